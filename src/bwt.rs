@@ -0,0 +1,173 @@
+//! Burrows-Wheeler Transform (BWT)
+//!
+//! The BWT of a string $T$ is the sequence of characters preceding the suffixes of $T\\$$
+//! in lexicographic order, where $\\$$ is a sentinel smaller than any character of $T$.
+//! The sentinel is represented by `None`.
+//!
+//! Without a sentinel, the BWT is defined over the rotations of $T$ in lexicographic order,
+//! and the original string is identified by the primary index, i.e., the rank of $T$ itself in the rotations.
+
+use crate::suffix_array::misc::SaIndex;
+use crate::suffix_array::sais::{make_sa_induce, IntoUsize};
+
+/// Returns the BWT of `text$` from the suffix array `sa` of `text`.
+pub fn bwt_from_sa<T, I>(text: &[T], sa: &[I]) -> Vec<Option<T>>
+where
+    T: Clone,
+    I: SaIndex,
+{
+    debug_assert_eq!(text.len(), sa.len());
+    // The suffix array of `text$` is `[n] + sa`.
+    std::iter::once(text.len())
+        .chain(sa.iter().map(|x| x.into_usize()))
+        .map(|i| match i {
+            0 => None,
+            _ => Some(text[i - 1].clone()),
+        })
+        .collect()
+}
+
+/// Returns the BWT of `text$`.
+pub fn bwt<T>(text: &[T]) -> Vec<Option<T>>
+where
+    T: Ord + Clone + IntoUsize,
+{
+    let sa: Vec<usize> = make_sa_induce(text);
+    bwt_from_sa(text, &sa)
+}
+
+/// Returns the BWT of the rotations of `text` and its primary index.
+pub fn bwt_rotation<T>(text: &[T]) -> (Vec<T>, usize)
+where
+    T: Ord + Clone + IntoUsize,
+{
+    let n = text.len();
+    // Rotations of `text` are sorted as the suffixes of `text+text` beginning in `text`.
+    let text2 = [text, text].concat();
+    let mut primary = 0;
+    let mut res = Vec::with_capacity(n);
+    let sa: Vec<usize> = make_sa_induce(&text2);
+    for i in sa.into_iter().filter(|&i| i < n) {
+        if i == 0 {
+            primary = res.len();
+        }
+        res.push(text[(i + n - 1) % n].clone());
+    }
+    (res, primary)
+}
+
+/// Returns LF-mapping of a given BWT.
+///
+/// `LF[i]` is the row of the suffix (rotation) beginning at `j-1`,
+/// where `i` is the row of the suffix (rotation) beginning at `j`.
+pub fn lf_array<T>(bwt: &[T]) -> Vec<usize>
+where
+    T: Ord + Clone,
+{
+    let mut alphabet = bwt.to_vec();
+    alphabet.sort();
+    alphabet.dedup();
+    let codes: Vec<usize> = bwt
+        .iter()
+        .map(|c| alphabet.binary_search(c).unwrap())
+        .collect();
+    let mut bkt = vec![0; alphabet.len() + 1];
+    codes.iter().for_each(|&c| bkt[c + 1] += 1);
+    for c in 1..bkt.len() {
+        bkt[c] += bkt[c - 1];
+    }
+    codes
+        .into_iter()
+        .map(|c| {
+            bkt[c] += 1;
+            bkt[c] - 1
+        })
+        .collect()
+}
+
+/// Returns the original text of the BWT of `text$`.
+pub fn inverse_bwt<T>(bwt: &[Option<T>]) -> Vec<T>
+where
+    T: Ord + Clone,
+{
+    assert_eq!(
+        bwt.iter().filter(|c| c.is_none()).count(),
+        1,
+        "BWT must contain exactly one sentinel."
+    );
+    let lf = lf_array(bwt);
+    let n = bwt.len() - 1;
+    let mut res = Vec::with_capacity(n);
+    // The row `0` is the suffix `$`, which is preceded by `text[n-1]`.
+    let mut i = 0;
+    for _ in 0..n {
+        res.push(bwt[i].clone().unwrap());
+        i = lf[i];
+    }
+    res.reverse();
+    res
+}
+
+/// Returns the original text of the BWT of rotations with its primary index.
+pub fn inverse_bwt_rotation<T>(bwt: &[T], primary: usize) -> Vec<T>
+where
+    T: Ord + Clone,
+{
+    if bwt.is_empty() {
+        return vec![];
+    }
+    assert!(primary < bwt.len());
+    let lf = lf_array(bwt);
+    let mut res = Vec::with_capacity(bwt.len());
+    let mut i = primary;
+    for _ in 0..bwt.len() {
+        res.push(bwt[i].clone());
+        i = lf[i];
+    }
+    res.reverse();
+    res
+}
+
+#[test]
+fn test_bwt() {
+    let text = br"banana";
+    let ans = vec![
+        Some(b'a'),
+        Some(b'n'),
+        Some(b'n'),
+        Some(b'b'),
+        None,
+        Some(b'a'),
+        Some(b'a'),
+    ];
+    assert_eq!(bwt(text), ans);
+    assert_eq!(inverse_bwt(&ans), text.to_vec());
+    assert_eq!(bwt_rotation(text), (br"nnbaaa".to_vec(), 3));
+    assert_eq!(inverse_bwt_rotation(br"nnbaaa", 3), text.to_vec());
+
+    assert_eq!(bwt::<u8>(&[]), vec![None]);
+    assert!(inverse_bwt::<u8>(&[None]).is_empty());
+    assert_eq!(bwt_rotation::<u8>(&[]), (vec![], 0));
+}
+
+#[test]
+fn test_bwt_inverse() {
+    use crate::fib::fibstr_ab;
+
+    let texts = vec![
+        br"a".to_vec(),
+        br"abab".to_vec(),
+        br"mississippi".to_vec(),
+        br"aaaaaaa".to_vec(),
+        fibstr_ab(12),
+    ];
+    for text in texts {
+        assert_eq!(inverse_bwt(&bwt(&text)), text);
+        let (l, primary) = bwt_rotation(&text);
+        assert_eq!(inverse_bwt_rotation(&l, primary), text);
+    }
+    let text: Vec<u16> = vec![200, 2, 200, 2, 1, 200];
+    assert_eq!(inverse_bwt(&bwt(&text)), text);
+    let (l, primary) = bwt_rotation(&text);
+    assert_eq!(inverse_bwt_rotation(&l, primary), text);
+}
@@ -1,35 +1,284 @@
-// use std::collections::HashSet;
-
-// use crate::bstr::*;
-// use crate::commons::*;
-// use crate::stree_json;
-
-// pub fn cdawg(text: &bstr) -> stree_json::StreeSerde {
-//     let subs = substrs(text);
-//     let lgmap = bpos_groups(text);
-//     let rgmap = epos_groups(text);
-//     let rm_req = |x: &Vec<_>| left_maximal(&right_maximal(&x, &lgmap), &rgmap);
-//     let sigma = alphabet_set(text);
-
-//     let vs: HashSet<BString> = subs.iter().cloned().map(|x| rm_req(&x)).collect();
-//     let nodes = vs
-//         .iter()
-//         .map(|x| String::from_utf8(x.to_vec()).unwrap())
-//         .collect();
-//     let mut edges = vec![];
-
-//     for v1 in vs {
-//         let v1str = bstr2string(&v1);
-//         for c in sigma.iter() {
-//             // let v1cvec = [v1.to_vec(), c.to_vec()].concat();
-//             let v1cvec = [v1.clone(), c.clone()].concat();
-//             // let v1c = v1cvec.as_slice();
-//             if subs.contains(&v1cvec) {
-//                 let label = bstr2string(&right_maximal(&v1cvec, &lgmap)[v1.len()..]);
-//                 let v2str = bstr2string(&left_maximal(&right_maximal(&v1cvec, &lgmap), &rgmap));
-//                 edges.push((v1str.clone(), label, v2str));
-//             }
-//         }
-//     }
-//     stree_json::StreeSerde { nodes, edges }
-// }
+//! Compact Directed Acyclic Word Graph (CDAWG)
+//!
+//! The CDAWG of a string $T$ is the minimal compact automaton accepting all the substrings of $T$.
+//! Its nodes are the empty string, $T$, and the maximal repeats of $T$,
+//! where repeats that are suffixes of $T$ are regarded as right-maximal.
+//! The number $e$ of its edges is a measure of the repetitiveness of $T$.
+//!
+//! It is built by minimizing the suffix tree of $T\\$$:
+//! all the leaves are merged into the sink,
+//! and internal nodes $cw$ and $w$ for a character $c$ are merged if they have the same number of occurrences.
+//! The suffix tree is built in $O(n \log \sigma)$ time, and the minimization takes expected $O(n)$ time
+//! by visiting the internal nodes bucketed by their depths, with hash tables of edges.
+
+use std::{collections::HashMap, hash::Hash, ops::Range};
+
+use crate::stree_json::{Label, StreeSerde};
+use crate::suffix_tree::ukkonnen_vec_lib::Tree;
+
+struct Edge {
+    /// Range of the text where the edge label occurs.
+    label: Range<usize>,
+    dst: usize,
+}
+
+struct Node<T> {
+    /// Range of the text where the longest string of the node occurs.
+    label: Range<usize>,
+    /// Number of occurrences of the strings of the node.
+    occ: usize,
+    edges: HashMap<T, Edge>,
+}
+
+/// CDAWG of a text.
+pub struct CDAWG<T> {
+    text: Vec<T>,
+    nodes: Vec<Node<T>>,
+}
+
+impl<T> CDAWG<T>
+where
+    T: Ord + Hash + Clone,
+{
+    /// Builds the CDAWG of a given text via its suffix tree.
+    pub fn from_text(text: &[T]) -> Self {
+        Self::from_tree(&Tree::from_text_sa(text))
+    }
+
+    /// Builds the CDAWG by minimizing a finished suffix tree.
+    pub fn from_tree(tree: &Tree<T>) -> Self {
+        assert!(tree.is_finished(), "the tree must be finished");
+        let text = tree.text();
+        let n = text.len();
+
+        let occs = tree.leaf_counts();
+
+        // `reps[v]` is the node of the CDAWG that node `v` of the suffix tree is merged into,
+        // and `members[u]` is the deepest node of the suffix tree merged into node `u` of the CDAWG,
+        // where the sink has no members.
+        let mut nodes = vec![Node {
+            label: 0..0,
+            occ: n,
+            edges: HashMap::new(),
+        }];
+        let mut members = vec![Some(tree.root())];
+        if n > 0 {
+            nodes.push(Node {
+                label: 0..n,
+                occ: 1,
+                edges: HashMap::new(),
+            });
+            members.push(None);
+        }
+        let mut reps = vec![None; tree.num_nodes()];
+        reps[tree.root()] = Some(0);
+        // Internal nodes are bucketed by their depths, which are less than `n`, from the deepest.
+        let mut buckets = vec![vec![]; n];
+        (0..tree.num_nodes())
+            .filter(|&nid| nid != tree.root() && !tree.is_leaf(nid))
+            .for_each(|nid| buckets[tree.depth(nid)].push(nid));
+        for nid in buckets.into_iter().rev().flatten() {
+            let rep = *reps[nid].get_or_insert_with(|| {
+                nodes.push(Node {
+                    label: tree.label_range(nid),
+                    occ: occs[nid],
+                    edges: HashMap::new(),
+                });
+                members.push(Some(nid));
+                nodes.len() - 1
+            });
+            let slink = tree.suffix_link(nid).unwrap();
+            if slink != tree.root() && occs[slink] == occs[nid] {
+                reps[slink] = Some(rep);
+            }
+        }
+
+        // Edges are copied from the deepest members, ignoring the ones of the terminator.
+        for (rep, nid) in members.into_iter().enumerate() {
+            let Some(nid) = nid else { continue };
+            for cid in tree.children(nid) {
+                let label = tree.edge_range(cid);
+                if label.is_empty() {
+                    continue;
+                }
+                let dst = if tree.is_leaf(cid) {
+                    1
+                } else {
+                    reps[cid].unwrap()
+                };
+                nodes[rep]
+                    .edges
+                    .insert(text[label.start].clone(), Edge { label, dst });
+            }
+        }
+        Self {
+            text: text.to_vec(),
+            nodes,
+        }
+    }
+
+    /// Returns the text.
+    pub fn text(&self) -> &[T] {
+        &self.text
+    }
+
+    /// Returns the ID of the source node, which corresponds to the empty string.
+    pub const fn root(&self) -> usize {
+        0
+    }
+
+    /// Returns the ID of the sink node, which corresponds to the text, if the text is not empty.
+    pub fn sink(&self) -> Option<usize> {
+        (!self.text.is_empty()).then_some(1)
+    }
+
+    /// Returns the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges, i.e., the repetitiveness measure $e$.
+    pub fn num_edges(&self) -> usize {
+        self.nodes.iter().map(|node| node.edges.len()).sum()
+    }
+
+    /// Returns the longest string of node `nid`.
+    pub fn label(&self, nid: usize) -> &[T] {
+        &self.text[self.nodes[nid].label.clone()]
+    }
+
+    /// Returns the edges of node `nid` as pairs of their labels and destinations
+    /// in the lexicographic order.
+    pub fn edges(&self, nid: usize) -> Vec<(&[T], usize)> {
+        let mut edges: Vec<(&[T], usize)> = self.nodes[nid]
+            .edges
+            .values()
+            .map(|edge| (&self.text[edge.label.clone()], edge.dst))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    /// Returns the node reached by the shortest path spelling a string that begins with a given pattern.
+    pub fn find(&self, pattern: &[T]) -> Option<usize> {
+        let mut nid = self.root();
+        let mut i = 0;
+        while i < pattern.len() {
+            let edge = self.nodes[nid].edges.get(&pattern[i])?;
+            let label = &self.text[edge.label.clone()];
+            let len = label.len().min(pattern.len() - i);
+            if label[..len] != pattern[i..i + len] {
+                return None;
+            }
+            i += len;
+            nid = edge.dst;
+        }
+        Some(nid)
+    }
+
+    /// Returns whether a given pattern is a substring of the text.
+    pub fn contains(&self, pattern: &[T]) -> bool {
+        self.find(pattern).is_some()
+    }
+
+    /// Returns the number of occurrences of a given pattern.
+    pub fn count(&self, pattern: &[T]) -> usize {
+        self.find(pattern).map_or(0, |nid| self.nodes[nid].occ)
+    }
+}
+
+impl<T> CDAWG<T>
+where
+    T: Ord + Hash + Clone + Label,
+{
+    /// Returns the CDAWG as a tree structure, whose nodes are identified by their longest strings.
+    pub fn to_stree(&self) -> StreeSerde {
+        let nodes = (0..self.nodes.len())
+            .map(|nid| T::label(self.label(nid)))
+            .collect();
+        let mut edges = vec![];
+        for nid in 0..self.nodes.len() {
+            for (label, dst) in self.edges(nid) {
+                edges.push((
+                    T::label(self.label(nid)),
+                    T::label(label),
+                    T::label(self.label(dst)),
+                ));
+            }
+        }
+        StreeSerde { nodes, edges }
+    }
+
+    pub fn serialize(&self) -> String {
+        self.to_stree().serialize()
+    }
+}
+
+#[test]
+fn test_cdawg() {
+    let cdawg = CDAWG::from_text(br"abab");
+    assert_eq!(cdawg.num_nodes(), 3);
+    assert_eq!(cdawg.num_edges(), 3);
+    let ab = cdawg.find(br"a").unwrap();
+    assert_eq!(cdawg.label(ab), br"ab");
+    assert_eq!(cdawg.find(br"b"), Some(ab));
+    assert_eq!(cdawg.edges(ab), vec![(&br"ab"[..], cdawg.sink().unwrap())]);
+
+    let cdawg = CDAWG::from_text(br"banana");
+    assert!(cdawg.contains(br"nan"));
+    assert!(!cdawg.contains(br"nab"));
+    assert_eq!(cdawg.count(br"ana"), 2);
+    assert_eq!(cdawg.count(br"a"), 3);
+    assert_eq!(cdawg.count(br"banana"), 1);
+    assert_eq!(cdawg.count(br"x"), 0);
+
+    let cdawg = CDAWG::<u8>::from_text(br"");
+    assert_eq!(cdawg.num_nodes(), 1);
+    assert_eq!(cdawg.num_edges(), 0);
+    assert_eq!(cdawg.sink(), None);
+}
+
+#[test]
+fn test_cdawg_naive() {
+    use crate::commons::{
+        alphabet_set, bpos_groups, epos_groups, left_maximal, locate_naive, right_maximal, substrs,
+        test_patterns, test_texts,
+    };
+    use std::collections::HashSet;
+
+    for text in test_texts() {
+        let cdawg = CDAWG::from_text(&text);
+        for pat in test_patterns(&text).iter().filter(|x| !x.is_empty()) {
+            assert_eq!(cdawg.count(pat), locate_naive(&text, pat).len());
+        }
+
+        // The longest string with the same beginning positions and then the same ending positions,
+        // which is computed naively only for short texts.
+        if text.is_empty() || text.len() > 12 {
+            continue;
+        }
+        let subs = substrs(&text);
+        let bgmap = bpos_groups(&text);
+        let egmap = epos_groups(&text);
+        let rmax = |x: &[u8]| right_maximal(x, &bgmap);
+        let node = |x: &[u8]| left_maximal(&rmax(x), &egmap);
+        let nodes: HashSet<Vec<u8>> = subs.iter().map(|x| node(x)).collect();
+        let mut edges = HashSet::new();
+        for v in nodes.iter() {
+            for c in alphabet_set(&text) {
+                let vc = [v.clone(), vec![c]].concat();
+                if subs.contains(&vc) {
+                    let label = rmax(&vc)[v.len()..].to_vec();
+                    edges.insert((u8::label(v), u8::label(&label), u8::label(&node(&vc))));
+                }
+            }
+        }
+        let nodes: HashSet<String> = nodes.iter().map(|x| u8::label(x)).collect();
+
+        let stree = cdawg.to_stree();
+        assert_eq!(stree.nodes.len(), nodes.len());
+        assert_eq!(stree.nodes.into_iter().collect::<HashSet<_>>(), nodes);
+        assert_eq!(stree.edges.len(), edges.len());
+        assert_eq!(stree.edges.into_iter().collect::<HashSet<_>>(), edges);
+    }
+}
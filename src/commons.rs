@@ -13,7 +13,7 @@ where
 
 /// Returns all prefixes of a given string.
 pub fn prefs_sli<T>(text: &[T]) -> Vec<&[T]> {
-    (0..=text.len()).into_iter().map(|i| &text[..i]).collect()
+    (0..=text.len()).map(|i| &text[..i]).collect()
 }
 
 /// Returns all suffixes of a given string.
@@ -26,7 +26,7 @@ where
 
 /// Returns all suffixes of a given string.
 pub fn sufs_sli<T>(text: &[T]) -> Vec<&[T]> {
-    (0..=text.len()).into_iter().map(|i| &text[i..]).collect()
+    (0..=text.len()).map(|i| &text[i..]).collect()
 }
 
 /// Returns a set of all prefixes of a given string.
@@ -90,8 +90,7 @@ where
     T: Hash + Eq,
 {
     (0..text.len())
-        .into_iter()
-        .flat_map(|i| (i..=text.len()).into_iter().map(move |j| &text[i..j]))
+        .flat_map(|i| (i..=text.len()).map(move |j| &text[i..j]))
         .collect()
 }
 
@@ -154,9 +153,8 @@ where
     T: Clone + Eq + Hash,
 {
     ss.iter()
+        .filter(|&s| pre.len() <= s.len() && *pre == s[..pre.len()])
         .cloned()
-        // .filter(|s| pre.len() <= s.len() && s.starts_with(pre))
-        .filter(|s| pre.len() <= s.len() && *pre == s[..pre.len()])
         // .filter(|s| pre.len() <= s.len())
         .collect()
 }
@@ -168,8 +166,8 @@ where
     T: Clone + Hash + Eq,
 {
     ss.iter()
+        .filter(|&s| suf.len() <= s.len() && s.ends_with(suf))
         .cloned()
-        .filter(|s| suf.len() <= s.len() && s.ends_with(suf))
         .collect()
 }
 
@@ -271,7 +269,7 @@ where
         return false;
     }
     for i in 1..text.len() {
-        if text.len() % i == 0 {
+        if text.len().is_multiple_of(i) {
             let k = text.len() / i;
             if repeat(&text[0..i], k) == text {
                 return false;
@@ -290,7 +288,7 @@ where
 {
     for i in 1..text.len() {
         let k = text.len() / i;
-        if text.len() % i == 0 && text == repeat(&text[..i], k) {
+        if text.len().is_multiple_of(i) && text == repeat(&text[..i], k) {
             return k;
         }
     }
@@ -329,10 +327,7 @@ pub fn enum_rotate_left<T>(text: &[T]) -> Vec<Vec<T>>
 where
     T: Clone,
 {
-    (0..text.len())
-        .into_iter()
-        .map(|i| rotate_left(text, i))
-        .collect()
+    (0..text.len()).map(|i| rotate_left(text, i)).collect()
 }
 
 /// Returns a set of all rotations of a given string.
@@ -456,6 +451,7 @@ where
 }
 
 #[test]
+#[allow(clippy::unnecessary_to_owned)]
 fn test_nocc() {
     let text = br"banana";
     let nocc = nocc(text);
@@ -512,7 +508,15 @@ pub fn end_pos<T>(w: &[T], x: &[T]) -> HashSet<usize>
 where
     T: PartialEq,
 {
-    (0..w.len()).filter(|i| w[*i..].ends_with(x)).collect()
+    (0..w.len()).filter(|i| w[..=*i].ends_with(x)).collect()
+}
+
+#[test]
+fn test_end_pos() {
+    let text = br"cocoaccao";
+    let pat = br"co";
+    let epos = end_pos(text, pat);
+    assert_eq!(epos, HashSet::from([1, 3]));
 }
 
 /// Checks whether ending positions of substrings `x` and `y` of `w` is equal.
@@ -588,6 +592,7 @@ where
 }
 
 #[test]
+#[allow(clippy::unnecessary_to_owned)]
 fn test_bpos_groups() {
     let text = br"cocoa";
     let bgroup = bpos_groups(text);
@@ -731,3 +736,109 @@ where
         .map(|i| text[i..i + q].to_vec())
         .collect()
 }
+
+/// Returns the failure function of the KMP algorithm,
+/// i.e., `fail[k]` is the length of the longest proper border of `pattern[..k+1]`.
+pub fn failure_function<T>(pattern: &[T]) -> Vec<usize>
+where
+    T: Eq,
+{
+    let mut fail = vec![0; pattern.len()];
+    let mut k = 0;
+    for i in 1..pattern.len() {
+        while k > 0 && pattern[i] != pattern[k] {
+            k = fail[k - 1];
+        }
+        if pattern[i] == pattern[k] {
+            k += 1;
+        }
+        fail[i] = k;
+    }
+    fail
+}
+
+/// Returns the number of occurrences of a given string `x` in `w`, i.e., the size of [`beg_pos`], by the KMP algorithm.
+pub fn count_occ<T>(w: &[T], x: &[T]) -> usize
+where
+    T: Eq,
+{
+    if x.is_empty() {
+        return w.len();
+    }
+    let fail = failure_function(x);
+    let mut count = 0;
+    let mut matched = 0;
+    for c in w.iter() {
+        while matched > 0 && (matched == x.len() || x[matched] != *c) {
+            matched = fail[matched - 1];
+        }
+        if x[matched] == *c {
+            matched += 1;
+        }
+        if matched == x.len() {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[test]
+fn test_count_occ() {
+    assert_eq!(failure_function(br"abaabab"), vec![0, 0, 1, 1, 2, 3, 2]);
+    assert_eq!(count_occ(br"abababa", br"aba"), 3);
+    assert_eq!(count_occ(br"aaaa", br"aa"), 3);
+    assert_eq!(count_occ(br"ab", br"abc"), 0);
+    assert_eq!(count_occ(br"ab", br""), 2);
+}
+
+/// Returns the beginning positions of a given string `x` in `w` in ascending order, as an oracle in tests.
+#[cfg(test)]
+pub fn locate_naive<T>(w: &[T], x: &[T]) -> Vec<usize>
+where
+    T: Eq,
+{
+    (0..w.len()).filter(|&i| w[i..].starts_with(x)).collect()
+}
+
+/// Returns texts shared by tests of indexes:
+/// the empty text, a long run, repetitive texts, and all the short texts over `{a, b}` and `{a, b, c}`.
+#[cfg(test)]
+pub fn test_texts() -> Vec<Vec<u8>> {
+    let mut texts = enum_strs_len_leq(br"ab", 6);
+    texts.extend(enum_strs_len_leq(br"abc", 4));
+    texts.extend([
+        br"abracadabra".to_vec(),
+        br"mississippi".to_vec(),
+        vec![b'a'; 30],
+        crate::fib::fibstr_ab(10),
+    ]);
+    texts.sort();
+    texts.dedup();
+    texts
+}
+
+/// Returns patterns to query on a given text in tests:
+/// the substrings of length at most `8` and the strings over `{a, b, c}` of length at most `3`,
+/// where the latter include ones that do not occur.
+#[cfg(test)]
+pub fn test_patterns(text: &[u8]) -> Vec<Vec<u8>> {
+    let mut pats: Vec<Vec<u8>> = substrs(text).into_iter().filter(|x| x.len() <= 8).collect();
+    pats.extend(enum_strs_len_leq(br"abc", 3));
+    pats.sort();
+    pats.dedup();
+    pats
+}
+
+#[test]
+fn test_test_texts() {
+    let texts = test_texts();
+    assert!(texts.contains(&vec![]));
+    assert!(texts.contains(&br"abc".to_vec()));
+    assert_eq!(locate_naive(br"abababa", br"aba"), vec![0, 2, 4]);
+    assert_eq!(locate_naive(br"ab", br""), vec![0, 1]);
+    for text in texts.iter().filter(|x| x.len() <= 6) {
+        for pat in test_patterns(text) {
+            assert_eq!(locate_naive(text, &pat).len(), count_occ(text, &pat));
+        }
+    }
+}
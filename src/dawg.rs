@@ -1,32 +1,315 @@
-// use crate::bstr::*;
-// use crate::commons::*;
-// use crate::stree_json;
-
-// pub fn dawg(text: &bstr) -> stree_json::StreeSerde {
-//     let subs = substrs(text);
-//     let rgmap = epos_groups(text);
-//     let sigma = alphabet_set(text);
-
-//     let rmaxs = rgmap
-//         .values()
-//         .cloned()
-//         .map(|xs| xs.into_iter().max_by_key(|x| x.len()).unwrap())
-//         .collect();
-//     println!("rmaxs={:?}", bstrs2string_set(&rmaxs));
-//     let nodes = rmaxs.iter().cloned().map(|x| bstr2string(&x)).collect();
-//     let mut edges = vec![];
-
-//     for v1 in rmaxs {
-//         for c in &sigma {
-//             // let v1cvec = [v1.clone(), c.clone()].concat();
-//             let mut v1cvec = v1.clone();
-//             v1cvec.push(*c);
-//             let v1c = v1cvec.as_slice();
-//             if subs.contains(v1c) {
-//                 let v2 = left_maximal(&v1c.to_vec(), &rgmap);
-//                 edges.push((bstr2string(&v1), [c], bstr2string(&v2)));
-//             }
-//         }
-//     }
-//     stree_json::StreeSerde { nodes, edges }
-// }
+//! Directed Acyclic Word Graph (DAWG)
+//!
+//! The DAWG (suffix automaton) of a string $T$ is the smallest automaton accepting all the substrings of $T$.
+//! Each node corresponds to a set of substrings that have the same ending positions in $T$,
+//! and is identified by the longest one.
+//! It is built online from left to right in expected $O(n)$ time with hash tables of transitions (Blumer et al., 1985).
+
+use std::{cell::OnceCell, collections::HashMap, hash::Hash};
+
+use crate::stree_json::{Label, StreeSerde};
+
+struct Node<T> {
+    /// Length of the longest string of the node.
+    len: usize,
+    /// Node of the longest suffix of the strings that belongs to another node.
+    slink: Option<usize>,
+    /// End position of the first occurrence of the strings.
+    first_end: usize,
+    /// Whether the node is cloned from another node, i.e., it does not correspond to a prefix of the text.
+    cloned: bool,
+    edges: HashMap<T, usize>,
+}
+
+/// DAWG built online.
+pub struct DAWG<T> {
+    text: Vec<T>,
+    nodes: Vec<Node<T>>,
+    /// Node of the whole text.
+    last: usize,
+    /// Number of distinct non-empty substrings.
+    num_substrs: usize,
+    /// Numbers of occurrences of the strings of nodes, computed on demand.
+    occs: OnceCell<Vec<usize>>,
+}
+
+impl<T> DAWG<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// Creates the DAWG of the empty string.
+    pub fn new() -> Self {
+        Self {
+            text: vec![],
+            nodes: vec![Node {
+                len: 0,
+                slink: None,
+                first_end: 0,
+                cloned: false,
+                edges: HashMap::new(),
+            }],
+            last: 0,
+            num_substrs: 0,
+            occs: OnceCell::new(),
+        }
+    }
+
+    /// Builds the DAWG of a given text.
+    pub fn from_text(text: &[T]) -> Self {
+        let mut dawg = Self::new();
+        text.iter().for_each(|c| dawg.add(c.clone()));
+        dawg
+    }
+
+    /// Appends a character to the text.
+    pub fn add(&mut self, c: T) {
+        self.occs.take();
+        self.text.push(c.clone());
+        let cur = self.nodes.len();
+        self.nodes.push(Node {
+            len: self.text.len(),
+            slink: None,
+            first_end: self.text.len() - 1,
+            cloned: false,
+            edges: HashMap::new(),
+        });
+
+        // Adds edges with `c` to `cur` along the suffix links until a node already has an edge with `c`.
+        let mut p = Some(self.last);
+        while let Some(x) = p {
+            if self.nodes[x].edges.contains_key(&c) {
+                break;
+            }
+            self.nodes[x].edges.insert(c.clone(), cur);
+            p = self.nodes[x].slink;
+        }
+        let slink = match p {
+            None => 0,
+            Some(p) => {
+                let q = self.nodes[p].edges[&c];
+                if self.nodes[p].len + 1 == self.nodes[q].len {
+                    q
+                } else {
+                    // Splits `q` into the strings of length at most `len(p)+1` and the others.
+                    let clone = self.nodes.len();
+                    self.nodes.push(Node {
+                        len: self.nodes[p].len + 1,
+                        slink: self.nodes[q].slink,
+                        first_end: self.nodes[q].first_end,
+                        cloned: true,
+                        edges: self.nodes[q].edges.clone(),
+                    });
+                    self.nodes[q].slink = Some(clone);
+                    let mut p = Some(p);
+                    while let Some(x) = p {
+                        match self.nodes[x].edges.get_mut(&c) {
+                            Some(y) if *y == q => *y = clone,
+                            _ => break,
+                        }
+                        p = self.nodes[x].slink;
+                    }
+                    clone
+                }
+            }
+        };
+        self.nodes[cur].slink = Some(slink);
+        self.num_substrs += self.nodes[cur].len - self.nodes[slink].len;
+        self.last = cur;
+    }
+
+    /// Returns the text.
+    pub fn text(&self) -> &[T] {
+        &self.text
+    }
+
+    /// Returns the ID of the source node, which corresponds to the empty string.
+    pub const fn root(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges.
+    pub fn num_edges(&self) -> usize {
+        self.nodes.iter().map(|node| node.edges.len()).sum()
+    }
+
+    /// Returns the node reached from node `nid` by character `c`.
+    pub fn edge(&self, nid: usize, c: &T) -> Option<usize> {
+        self.nodes[nid].edges.get(c).copied()
+    }
+
+    /// Returns the node that the suffix link of node `nid` points to.
+    pub fn suffix_link(&self, nid: usize) -> Option<usize> {
+        self.nodes[nid].slink
+    }
+
+    /// Returns the longest string of node `nid`.
+    pub fn label(&self, nid: usize) -> &[T] {
+        let node = &self.nodes[nid];
+        if node.len == 0 {
+            &[]
+        } else {
+            &self.text[node.first_end + 1 - node.len..=node.first_end]
+        }
+    }
+
+    /// Returns the node reached by a given pattern.
+    pub fn find(&self, pattern: &[T]) -> Option<usize> {
+        pattern
+            .iter()
+            .try_fold(self.root(), |nid, c| self.edge(nid, c))
+    }
+
+    /// Returns whether a given pattern is a substring of the text.
+    pub fn contains(&self, pattern: &[T]) -> bool {
+        self.find(pattern).is_some()
+    }
+
+    /// Returns the number of occurrences of the strings of each node.
+    ///
+    /// Each prefix of the text is counted at its own node,
+    /// and the counts are accumulated along suffix links from longer nodes to shorter ones.
+    fn occs(&self) -> &[usize] {
+        self.occs.get_or_init(|| {
+            let mut occs: Vec<usize> = self
+                .nodes
+                .iter()
+                .map(|node| usize::from(!node.cloned))
+                .collect();
+            let mut order: Vec<usize> = (1..self.nodes.len()).collect();
+            order.sort_by_key(|&nid| std::cmp::Reverse(self.nodes[nid].len));
+            for nid in order {
+                let slink = self.nodes[nid].slink.unwrap();
+                occs[slink] += occs[nid];
+            }
+            occs[self.root()] = self.text.len();
+            occs
+        })
+    }
+
+    /// Returns the number of occurrences of a given pattern.
+    pub fn count(&self, pattern: &[T]) -> usize {
+        self.find(pattern).map_or(0, |nid| self.occs()[nid])
+    }
+
+    /// Returns the beginning position of the first occurrence of a given pattern.
+    pub fn first_occurrence(&self, pattern: &[T]) -> Option<usize> {
+        if pattern.is_empty() {
+            return Some(0);
+        }
+        self.find(pattern)
+            .map(|nid| self.nodes[nid].first_end + 1 - pattern.len())
+    }
+
+    /// Returns the number of distinct non-empty substrings of the text.
+    pub fn num_substrs(&self) -> usize {
+        self.num_substrs
+    }
+}
+
+impl<T> DAWG<T>
+where
+    T: Ord + Hash + Clone + Label,
+{
+    /// Returns the DAWG as a tree structure, whose nodes are identified by their longest strings.
+    ///
+    /// The edges of each node are listed in the order of their characters.
+    pub fn to_stree(&self) -> StreeSerde {
+        let nodes = (0..self.nodes.len())
+            .map(|nid| T::label(self.label(nid)))
+            .collect();
+        let mut edges = vec![];
+        for (nid, node) in self.nodes.iter().enumerate() {
+            let mut out: Vec<(&T, usize)> = node.edges.iter().map(|(c, &cid)| (c, cid)).collect();
+            out.sort_unstable();
+            for (c, cid) in out {
+                edges.push((
+                    T::label(self.label(nid)),
+                    T::label(std::slice::from_ref(c)),
+                    T::label(self.label(cid)),
+                ));
+            }
+        }
+        StreeSerde { nodes, edges }
+    }
+
+    pub fn serialize(&self) -> String {
+        self.to_stree().serialize()
+    }
+}
+
+impl<T> Default for DAWG<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_dawg() {
+    let dawg = DAWG::from_text(br"banana");
+    assert!(dawg.contains(br"nan"));
+    assert!(!dawg.contains(br"nab"));
+    assert_eq!(dawg.count(br"a"), 3);
+    assert_eq!(dawg.count(br"ana"), 2);
+    assert_eq!(dawg.count(br"x"), 0);
+    assert_eq!(dawg.first_occurrence(br"na"), Some(2));
+    assert_eq!(dawg.first_occurrence(br"nab"), None);
+    assert_eq!(dawg.num_substrs(), 15);
+    let nid = dawg.find(br"na").unwrap();
+    assert_eq!(dawg.label(nid), br"ana");
+    assert_eq!(dawg.label(dawg.suffix_link(nid).unwrap()), br"a");
+    // The serialization does not depend on the iteration order of the hash tables.
+    assert_eq!(dawg.serialize(), DAWG::from_text(br"banana").serialize());
+    let edges = dawg.to_stree().edges;
+    assert_eq!(edges[0].1, "a");
+    assert_eq!(edges[1].1, "b");
+    assert_eq!(edges[2].1, "n");
+}
+
+#[test]
+fn test_dawg_naive() {
+    use crate::commons::{
+        epos_groups, left_maximal, locate_naive, substrs, test_patterns, test_texts,
+    };
+    use std::collections::HashSet;
+
+    for text in test_texts() {
+        let dawg = DAWG::from_text(&text);
+        let subs = substrs(&text);
+        assert_eq!(dawg.num_substrs(), subs.len().saturating_sub(1));
+        for pat in test_patterns(&text).iter().filter(|x| !x.is_empty()) {
+            let ans = locate_naive(&text, pat);
+            assert_eq!(dawg.count(pat), ans.len());
+            assert_eq!(dawg.first_occurrence(pat), ans.first().copied());
+        }
+
+        // Nodes are the longest strings of the groups of the same ending positions,
+        // which are computed naively only for short texts.
+        if text.is_empty() || text.len() > 12 {
+            continue;
+        }
+        let egmap = epos_groups(&text);
+        let lmax = |x: &[u8]| String::from_utf8(left_maximal(x, &egmap)).unwrap();
+        let nodes: HashSet<String> = subs.iter().map(|x| lmax(x)).collect();
+        let edges: HashSet<(String, String, String)> = subs
+            .iter()
+            .filter(|x| !x.is_empty())
+            .map(|x| {
+                let (pre, c) = x.split_at(x.len() - 1);
+                (lmax(pre), u8::label(c), lmax(x))
+            })
+            .collect();
+        let stree = dawg.to_stree();
+        assert_eq!(stree.nodes.len(), nodes.len());
+        assert_eq!(stree.nodes.into_iter().collect::<HashSet<_>>(), nodes);
+        assert_eq!(stree.edges.len(), edges.len());
+        assert_eq!(stree.edges.into_iter().collect::<HashSet<_>>(), edges);
+    }
+}
@@ -58,7 +58,9 @@ where
 /// Compute the divisors of a given integer.
 fn divisors(n: usize) -> Vec<usize> {
     let high = (n as f64).sqrt().floor() as usize;
-    let mut res = (1..=high).filter(|&i| n % i == 0).collect::<Vec<usize>>();
+    let mut res = (1..=high)
+        .filter(|&i| n.is_multiple_of(i))
+        .collect::<Vec<usize>>();
     res.push(n);
     res
 }
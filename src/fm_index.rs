@@ -0,0 +1,393 @@
+//! FM-index
+//!
+//! A compressed full-text index based on the Burrows-Wheeler transform (BWT) of $T\\$$,
+//! where $\\$$ is a sentinel smaller than any character of $T$.
+//! It supports counting, locating occurrences of patterns and extracting substrings
+//! without keeping the text itself.
+//!
+//! The BWT is stored in a wavelet matrix, which takes $n \lceil \log_2 (\sigma+1) \rceil$ bits plus rank samples,
+//! where $\sigma$ is the number of distinct characters of $T$.
+//! Rank and access queries on the BWT take $O(\log \sigma)$ time regardless of the type of characters.
+
+use std::ops::Range;
+
+use crate::bwt::bwt_from_sa;
+use crate::suffix_array::sais::make_sa_induce;
+
+/// Default sampling interval of the suffix array.
+const DEFAULT_SA_RATE: usize = 16;
+
+/// Code of the sentinel in the BWT.
+const SENTINEL: usize = 0;
+
+/// Number of 64-bit words per rank sample.
+const RANK_WORDS: usize = 8;
+
+/// Bit vector supporting rank queries.
+struct RankBitVec {
+    bits: Vec<u64>,
+    /// `ranks[k]` is the number of set bits in the first `k*RANK_WORDS` words.
+    ranks: Vec<usize>,
+}
+
+impl RankBitVec {
+    fn new<I>(bools: I) -> Self
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let mut bits: Vec<u64> = vec![];
+        for (i, b) in bools.into_iter().enumerate() {
+            if i % 64 == 0 {
+                bits.push(0);
+            }
+            if b {
+                bits[i / 64] |= 1 << (i % 64);
+            }
+        }
+        // A sentinel word makes `rank1(len)` valid.
+        bits.push(0);
+        let mut ranks = Vec::with_capacity(bits.len() / RANK_WORDS + 1);
+        let mut sum = 0;
+        for (k, w) in bits.iter().enumerate() {
+            if k % RANK_WORDS == 0 {
+                ranks.push(sum);
+            }
+            sum += w.count_ones() as usize;
+        }
+        Self { bits, ranks }
+    }
+
+    #[inline(always)]
+    fn get(&self, i: usize) -> bool {
+        (self.bits[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    /// Returns the number of set bits in `[0, i)`.
+    #[inline(always)]
+    fn rank1(&self, i: usize) -> usize {
+        let (k, r) = (i / 64, i % 64);
+        let blk = k / RANK_WORDS;
+        let words: usize = self.bits[blk * RANK_WORDS..k]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum();
+        self.ranks[blk] + words + (self.bits[k] & ((1u64 << r) - 1)).count_ones() as usize
+    }
+
+    /// Returns the number of unset bits in `[0, i)`.
+    #[inline(always)]
+    fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+}
+
+/// Wavelet matrix over codes of `width` bits, which supports access and rank queries in $O(width)$ time
+/// using $n \cdot width$ bits and small rank samples.
+struct WaveletMatrix {
+    /// `levels[l]` stores the `l`-th highest bits of the codes, reordered stably by the higher bits.
+    levels: Vec<RankBitVec>,
+    /// `zeros[l]` is the number of unset bits of `levels[l]`.
+    zeros: Vec<usize>,
+}
+
+impl WaveletMatrix {
+    fn new(mut codes: Vec<usize>, width: usize) -> Self {
+        let mut levels = Vec::with_capacity(width);
+        let mut zeros = Vec::with_capacity(width);
+        for l in (0..width).rev() {
+            let bit = |c: usize| (c >> l) & 1 == 1;
+            levels.push(RankBitVec::new(codes.iter().map(|&c| bit(c))));
+            let (mut lo, hi): (Vec<usize>, Vec<usize>) = codes.into_iter().partition(|&c| !bit(c));
+            zeros.push(lo.len());
+            lo.extend(hi);
+            codes = lo;
+        }
+        Self { levels, zeros }
+    }
+
+    /// Returns the code at position `i`.
+    fn access(&self, mut i: usize) -> usize {
+        let mut c = 0;
+        for (level, &zeros) in self.levels.iter().zip(self.zeros.iter()) {
+            let b = level.get(i);
+            c = (c << 1) | b as usize;
+            i = if b {
+                zeros + level.rank1(i)
+            } else {
+                level.rank0(i)
+            };
+        }
+        c
+    }
+
+    /// Returns the number of occurrences of code `c` in `[0, i)`.
+    fn rank(&self, c: usize, i: usize) -> usize {
+        let (mut beg, mut end) = (0, i);
+        let width = self.levels.len();
+        for (l, (level, &zeros)) in self.levels.iter().zip(self.zeros.iter()).enumerate() {
+            if (c >> (width - 1 - l)) & 1 == 1 {
+                beg = zeros + level.rank1(beg);
+                end = zeros + level.rank1(end);
+            } else {
+                beg = level.rank0(beg);
+                end = level.rank0(end);
+            }
+        }
+        end - beg
+    }
+}
+
+/// FM-index over a text of an ordered alphabet.
+pub struct FMIndex<T> {
+    /// Distinct characters of the text in ascending order.
+    /// A character `alphabet[c]` is encoded as `c+1` in the BWT.
+    alphabet: Vec<T>,
+    /// Length of the BWT, i.e., the length of the text with the sentinel.
+    len: usize,
+    /// BWT of the text with the sentinel, where each code takes $\lceil \log_2 (\sigma+1) \rceil$ bits.
+    bwt: WaveletMatrix,
+    /// `c_arr[c]` is the number of characters less than `c` in the text with the sentinel.
+    c_arr: Vec<usize>,
+    sa_rate: usize,
+    /// Marks rows of the BWT whose suffix array values are sampled.
+    sampled: RankBitVec,
+    /// Sampled suffix array values in the order of rows.
+    sa_samples: Vec<usize>,
+    /// `isa_samples[k]` is the row of the suffix beginning at `k*sa_rate`.
+    isa_samples: Vec<usize>,
+}
+
+impl<T> FMIndex<T>
+where
+    T: Ord + Clone,
+{
+    /// Builds an FM-index of a given text.
+    pub fn new(text: &[T]) -> Self {
+        Self::with_sa_rate(text, DEFAULT_SA_RATE)
+    }
+
+    /// Builds an FM-index of a given text sampling the suffix array every `sa_rate` positions.
+    pub fn with_sa_rate(text: &[T], sa_rate: usize) -> Self {
+        assert!(sa_rate > 0, "sa_rate must be positive");
+        let n = text.len();
+        let mut alphabet = text.to_vec();
+        alphabet.sort();
+        alphabet.dedup();
+        let codes: Vec<u32> = text
+            .iter()
+            .map(|c| alphabet.binary_search(c).unwrap() as u32)
+            .collect();
+        let sa: Vec<usize> = make_sa_induce(&codes);
+        let bwt: Vec<usize> = bwt_from_sa(&codes, &sa)
+            .into_iter()
+            .map(|c| c.map_or(SENTINEL, |c| c as usize + 1))
+            .collect();
+
+        // The suffix array of the text with the sentinel is `[n] + sa`.
+        let suf = |i: usize| if i == 0 { n } else { sa[i - 1] };
+
+        let sigma = alphabet.len() + 1;
+        let mut c_arr = vec![0; sigma + 1];
+        bwt.iter().for_each(|&c| c_arr[c + 1] += 1);
+        for c in 1..c_arr.len() {
+            c_arr[c] += c_arr[c - 1];
+        }
+        let width = (usize::BITS - (sigma - 1).leading_zeros()) as usize;
+
+        let marks: Vec<bool> = (0..=n)
+            .map(|i| suf(i) % sa_rate == 0 || suf(i) == n)
+            .collect();
+        let sa_samples = (0..=n).filter(|&i| marks[i]).map(suf).collect();
+        let mut isa_samples = vec![0; n.div_ceil(sa_rate)];
+        (0..=n)
+            .filter(|&i| suf(i) % sa_rate == 0 && suf(i) < n)
+            .for_each(|i| isa_samples[suf(i) / sa_rate] = i);
+
+        Self {
+            alphabet,
+            len: n + 1,
+            bwt: WaveletMatrix::new(bwt, width),
+            c_arr,
+            sa_rate,
+            sampled: RankBitVec::new(marks),
+            sa_samples,
+            isa_samples,
+        }
+    }
+
+    /// Returns the length of the text.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len - 1
+    }
+
+    /// Returns `true` if the text is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the code of a character, or `None` if it does not appear in the text.
+    fn code(&self, c: &T) -> Option<usize> {
+        self.alphabet.binary_search(c).ok().map(|c| c + 1)
+    }
+
+    /// Returns the number of occurrences of code `c` in `bwt[..i]`.
+    fn rank(&self, c: usize, i: usize) -> usize {
+        self.bwt.rank(c, i)
+    }
+
+    /// LF-mapping, which maps the row of a suffix beginning at `j` to the row of the suffix beginning at `j-1`.
+    #[inline(always)]
+    fn lf(&self, i: usize) -> usize {
+        let c = self.bwt.access(i);
+        self.c_arr[c] + self.rank(c, i)
+    }
+
+    /// Returns the interval of rows of the BWT whose suffixes begin with a given pattern.
+    /// The row `0` corresponds to the suffix consisting of the sentinel only.
+    pub fn range(&self, pattern: &[T]) -> Range<usize> {
+        if pattern.is_empty() {
+            return 1..self.len;
+        }
+        let (mut beg, mut end) = (0, self.len);
+        for x in pattern.iter().rev() {
+            let c = match self.code(x) {
+                Some(c) => c,
+                None => return 0..0,
+            };
+            beg = self.c_arr[c] + self.rank(c, beg);
+            end = self.c_arr[c] + self.rank(c, end);
+            if beg >= end {
+                return 0..0;
+            }
+        }
+        beg..end
+    }
+
+    /// Returns the number of occurrences of a given pattern.
+    pub fn count(&self, pattern: &[T]) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Returns the text position of the suffix at row `i`.
+    fn sa(&self, mut i: usize) -> usize {
+        let mut steps = 0;
+        while !self.sampled.get(i) {
+            i = self.lf(i);
+            steps += 1;
+        }
+        self.sa_samples[self.sampled.rank1(i)] + steps
+    }
+
+    /// Returns the beginning positions of occurrences of a given pattern in ascending order.
+    pub fn locate(&self, pattern: &[T]) -> Vec<usize> {
+        let mut res: Vec<usize> = self.range(pattern).map(|i| self.sa(i)).collect();
+        res.sort_unstable();
+        res
+    }
+
+    /// Returns the substring `text[range]`.
+    pub fn extract(&self, range: Range<usize>) -> Vec<T> {
+        assert!(range.start <= range.end && range.end <= self.len());
+        // Starts from the first sampled position at or after `range.end`.
+        let mut pos = range.end.div_ceil(self.sa_rate) * self.sa_rate;
+        let mut i = if pos >= self.len() {
+            pos = self.len();
+            0
+        } else {
+            self.isa_samples[pos / self.sa_rate]
+        };
+        let mut res = Vec::with_capacity(range.len());
+        while pos > range.start {
+            // `bwt[i]` is the character `text[pos-1]`.
+            if pos <= range.end {
+                res.push(self.alphabet[self.bwt.access(i) - 1].clone());
+            }
+            i = self.lf(i);
+            pos -= 1;
+        }
+        res.reverse();
+        res
+    }
+}
+
+#[test]
+fn test_fm_index() {
+    let text = br"mississippi";
+    let fm = FMIndex::new(text);
+    assert_eq!(fm.len(), text.len());
+    assert_eq!(fm.count(br"ssi"), 2);
+    assert_eq!(fm.count(br"i"), 4);
+    assert_eq!(fm.count(br"x"), 0);
+    assert_eq!(fm.count(br"ssissi"), 1);
+    assert_eq!(fm.count(br"pis"), 0);
+    assert_eq!(fm.count(br""), text.len());
+    assert_eq!(fm.locate(br"ssi"), vec![2, 5]);
+    assert_eq!(fm.locate(br"i"), vec![1, 4, 7, 10]);
+    assert_eq!(fm.locate(br"mississippi"), vec![0]);
+    assert_eq!(fm.extract(0..text.len()), text.to_vec());
+    assert_eq!(fm.extract(2..6), br"ssis".to_vec());
+    assert!(fm.extract(4..4).is_empty());
+}
+
+#[test]
+fn test_fm_index_naive() {
+    use crate::commons::{locate_naive, test_patterns, test_texts};
+
+    for text in test_texts() {
+        for sa_rate in [1, 3, 16] {
+            let fm = FMIndex::with_sa_rate(&text, sa_rate);
+            for pat in test_patterns(&text) {
+                let ans = locate_naive(&text, &pat);
+                assert_eq!(fm.count(&pat), ans.len());
+                assert_eq!(fm.locate(&pat), ans);
+            }
+            for i in 0..=text.len() {
+                for j in i..=text.len() {
+                    assert_eq!(fm.extract(i..j), text[i..j]);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fm_index_u32() {
+    let text: Vec<u32> = vec![100000, 7, 100000, 7, 3, 100000, 7];
+    let fm = FMIndex::new(&text);
+    assert_eq!(fm.locate(&[100000, 7]), vec![0, 2, 5]);
+    assert_eq!(fm.count(&[7, 100000]), 1);
+    assert_eq!(fm.count(&[8]), 0);
+    assert_eq!(fm.extract(1..5), text[1..5]);
+
+    // Each character of the BWT takes 12 bits for about 3000 distinct characters.
+    let text: Vec<u32> = (0..5000u32)
+        .map(|i| i.wrapping_mul(2654435761) % 4000)
+        .collect();
+    let fm = FMIndex::new(&text);
+    assert_eq!(fm.bwt.levels.len(), 12);
+    assert_eq!(fm.extract(0..text.len()), text);
+    for i in (0..text.len() - 3).step_by(97) {
+        let pat = &text[i..i + 3];
+        assert_eq!(fm.locate(pat), crate::commons::locate_naive(&text, pat));
+    }
+}
+
+#[test]
+fn test_wavelet_matrix() {
+    let codes: Vec<usize> = (0..1000usize).map(|i| (i * i * 7 + i / 3) % 37).collect();
+    let wm = WaveletMatrix::new(codes.clone(), 6);
+    for (i, &c) in codes.iter().enumerate() {
+        assert_eq!(wm.access(i), c);
+    }
+    for c in 0..40 {
+        let mut count = 0;
+        for i in 0..=codes.len() {
+            assert_eq!(wm.rank(c, i), count);
+            if i < codes.len() && codes[i] == c {
+                count += 1;
+            }
+        }
+    }
+}
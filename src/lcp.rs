@@ -1,31 +1,156 @@
+use crate::suffix_array::misc::SaIndex;
+
 /// Retruns LCP array.
-pub fn make_lcpa<T>(text: &[T], sa: &[usize]) -> Vec<usize>
+pub fn make_lcpa<T, I>(text: &[T], sa: &[I]) -> Vec<I>
 where
     T: Ord,
+    I: SaIndex,
 {
     make_lcpa_kasai(text, sa)
 }
 
 /// Retruns LCP array using Kasai algorithm.
-pub fn make_lcpa_kasai<T>(text: &[T], sa: &[usize]) -> Vec<usize>
+///
+/// It uses $n$ words for the rank array besides the output.
+pub fn make_lcpa_kasai<T, I>(text: &[T], sa: &[I]) -> Vec<I>
 where
     T: PartialEq,
+    I: SaIndex,
 {
     let n = text.len();
-    let mut rank = vec![0; text.len()];
-    let mut lcp = vec![0; text.len()];
+    let mut rank = vec![I::ZERO; text.len()];
+    let mut lcp = vec![I::ZERO; text.len()];
     for (i, &x) in sa.iter().enumerate() {
-        rank[x] = i;
+        rank[x.into_usize()] = I::from_usize(i);
+    }
+    let mut l = 0;
+    for i in 0..n {
+        if rank[i] == I::ZERO {
+            l = 0;
+            continue;
+        }
+        let j = sa[rank[i].into_usize() - 1].into_usize();
+        while i + l < n && j + l < n && text[i + l] == text[j + l] {
+            l += 1;
+        }
+        lcp[rank[i].into_usize()] = I::from_usize(l);
+        l = if l > 0 { l - 1 } else { 0 };
+    }
+    lcp
+}
+
+/// Returns permuted LCP array `plcp` using $\Phi$ algorithm,
+/// where `plcp[sa[i]] = lcp[i]`.
+///
+/// It works in place of the output, namely, no extra space is used besides the output.
+pub fn make_plcpa_phi<T, I>(text: &[T], sa: &[I]) -> Vec<I>
+where
+    T: PartialEq,
+    I: SaIndex,
+{
+    let n = text.len();
+    // `plcp[sa[i]]` temporarily stores `sa[i-1]`, that is $\Phi$.
+    let mut plcp = vec![I::MAX; n];
+    for i in 1..n {
+        plcp[sa[i].into_usize()] = sa[i - 1];
     }
     let mut l = 0;
-    for i in 0..n - 1 {
-        let j = sa[rank[i] - 1];
-        while (i + l < n) & (j + l < n) & (text[i + l] == text[j + l]) {
+    for i in 0..n {
+        if plcp[i] == I::MAX {
+            plcp[i] = I::ZERO;
+            l = 0;
+            continue;
+        }
+        let j = plcp[i].into_usize();
+        while i + l < n && j + l < n && text[i + l] == text[j + l] {
             l += 1;
         }
-        lcp[rank[i]] = l;
+        plcp[i] = I::from_usize(l);
         l = if l > 0 { l - 1 } else { 0 };
     }
+    plcp
+}
+
+/// Returns LCP array using $\Phi$ algorithm.
+///
+/// The permuted LCP array is computed in place of the output by [`make_plcpa_phi`],
+/// and then permuted into the LCP array in place following the cycles of `sa`.
+/// It uses $n$ bits to mark the visited positions besides the output.
+pub fn make_lcpa_phi<T, I>(text: &[T], sa: &[I]) -> Vec<I>
+where
+    T: PartialEq,
+    I: SaIndex,
+{
+    let n = text.len();
+    let mut lcp = make_plcpa_phi(text, sa);
+    let mut visited = vec![0u64; n.div_ceil(64)];
+    for i in 0..n {
+        if visited[i / 64] >> (i % 64) & 1 == 1 {
+            continue;
+        }
+        // Moves `lcp[sa[j]]` to `lcp[j]` along the cycle `i, sa[i], sa[sa[i]], ...`.
+        let first = lcp[i];
+        let mut j = i;
+        loop {
+            visited[j / 64] |= 1 << (j % 64);
+            let k = sa[j].into_usize();
+            if k == i {
+                lcp[j] = first;
+                break;
+            }
+            lcp[j] = lcp[k];
+            j = k;
+        }
+    }
+    lcp
+}
+
+/// Returns LCP array using sparse $\Phi$ algorithm (Kärkkäinen et al., 2009).
+///
+/// It computes the permuted LCP values only for text positions that are multiples of `q`,
+/// and then the other values using them as lower bounds.
+/// It uses $n/q$ words besides the output, and runs in $O(nq)$ time.
+pub fn make_lcpa_phi_sparse<T, I>(text: &[T], sa: &[I], q: usize) -> Vec<I>
+where
+    T: PartialEq,
+    I: SaIndex,
+{
+    assert!(q > 0, "q must be positive");
+    let n = text.len();
+    // `splcp[p/q]` temporarily stores $\Phi(p)$ for text positions `p` that are multiples of `q`.
+    let mut splcp = vec![I::MAX; n.div_ceil(q)];
+    for i in 1..n {
+        let p = sa[i].into_usize();
+        if p.is_multiple_of(q) {
+            splcp[p / q] = sa[i - 1];
+        }
+    }
+    let mut l = 0;
+    for (k, x) in splcp.iter_mut().enumerate() {
+        let p = k * q;
+        if *x == I::MAX {
+            *x = I::ZERO;
+            l = 0;
+            continue;
+        }
+        let j = x.into_usize();
+        while p + l < n && j + l < n && text[p + l] == text[j + l] {
+            l += 1;
+        }
+        *x = I::from_usize(l);
+        l = l.saturating_sub(q);
+    }
+
+    let mut lcp = vec![I::ZERO; n];
+    for i in 1..n {
+        let p = sa[i].into_usize();
+        let j = sa[i - 1].into_usize();
+        let mut l = splcp[p / q].into_usize().saturating_sub(p % q);
+        while p + l < n && j + l < n && text[p + l] == text[j + l] {
+            l += 1;
+        }
+        lcp[i] = I::from_usize(l);
+    }
     lcp
 }
 
@@ -33,8 +158,82 @@ where
 fn test_lcp() {
     use crate::suffix_array::sanaive::*;
     let text = br"bananaba$";
-    let sa = make_sa(text);
+    let sa: Vec<usize> = make_sa(text);
     let lcpa = make_lcpa(text, &sa);
     let ans = vec![0, 0, 1, 1, 3, 0, 2, 0, 2];
     assert_eq!(ans, lcpa);
 }
+
+#[test]
+fn test_lcp_phi() {
+    use crate::fib::fibstr_ab;
+    use crate::suffix_array::sais::make_sa_lcp_induce;
+    use crate::suffix_array::sanaive::*;
+
+    let texts = vec![
+        br"".to_vec(),
+        br"a".to_vec(),
+        br"aaaaaaa".to_vec(),
+        br"bananaba".to_vec(),
+        br"mississippi".to_vec(),
+        br"abracadabracadabra".to_vec(),
+        fibstr_ab(10),
+    ];
+    for text in texts {
+        let sa: Vec<u32> = make_sa(&text);
+        let ans: Vec<u32> = (0..text.len())
+            .map(|i| match i {
+                0 => 0,
+                _ => {
+                    let (x, y) = (&text[sa[i - 1] as usize..], &text[sa[i] as usize..]);
+                    x.iter().zip(y).take_while(|(a, b)| a == b).count() as u32
+                }
+            })
+            .collect();
+        assert_eq!(make_lcpa_kasai(&text, &sa), ans);
+        assert_eq!(make_lcpa_phi(&text, &sa), ans);
+        for q in [2, 3, 8] {
+            assert_eq!(make_lcpa_phi_sparse(&text, &sa, q), ans);
+        }
+        let plcp = make_plcpa_phi(&text, &sa);
+        assert!((0..text.len()).all(|i| plcp[sa[i] as usize] == ans[i]));
+        assert_eq!(make_sa_lcp_induce(&text), (sa, ans));
+    }
+}
+
+#[test]
+fn test_lcp_phi_random() {
+    use crate::suffix_array::sais::{make_sa_induce, make_sa_lcp_induce};
+
+    // Linear congruential generator for reproducible random texts.
+    let mut state = 86u64;
+    let mut random_text = |len: usize, sigma: u64| -> Vec<u32> {
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((state >> 33) % sigma) as u32
+            })
+            .collect()
+    };
+    for &(len, sigma) in &[
+        (20000, 2),
+        (20000, 4),
+        (20000, 256),
+        (50000, 3),
+        (1000, 100000),
+    ] {
+        let mut texts = vec![random_text(len, sigma)];
+        // Highly repetitive texts with long LCP values.
+        let unit = random_text(37, sigma);
+        texts.push(unit.iter().cycle().take(len).copied().collect());
+        for text in texts {
+            let sa: Vec<u32> = make_sa_induce(&text);
+            let ans = make_lcpa_kasai(&text, &sa);
+            assert_eq!(make_lcpa_phi(&text, &sa), ans);
+            assert_eq!(make_lcpa_phi_sparse(&text, &sa, 5), ans);
+            assert_eq!(make_sa_lcp_induce(&text), (sa, ans));
+        }
+    }
+}
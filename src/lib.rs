@@ -1,14 +1,21 @@
+pub mod bwt;
+pub mod cdawg;
 pub mod commons;
+pub mod dawg;
 pub mod debruijn;
 pub mod fib;
+pub mod fm_index;
 pub mod lcp;
 pub mod lyndon;
 pub mod lz77;
 pub mod mus;
 pub mod nss;
 pub mod palindrome;
+pub mod rlbwt;
 pub mod rlz;
 pub mod stree_json;
+pub mod strstat;
 pub mod suffix_array;
 pub mod suffix_tree;
+pub mod suffix_trie;
 pub mod utils;
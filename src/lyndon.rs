@@ -2,9 +2,9 @@ use std::hash::Hash;
 
 use crate::commons::*;
 
-/// Note that an empty string is not a lyndon word.
-
 /// Checks whether a given string is a lyndon word.
+///
+/// Note that an empty string is not a lyndon word.
 pub fn is_lyndon<T>(text: &[T]) -> bool
 where
     T: Clone + PartialOrd + Eq + Hash,
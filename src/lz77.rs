@@ -1,6 +1,125 @@
-use std::cmp::max;
+pub mod container;
+pub mod lzss;
+pub mod variants;
 
-use crate::suffix_array::sais::make_sa_induce;
+use std::{cmp::max, fmt};
+
+use crate::suffix_array::sais::{make_sa_induce, IntoUsize};
+
+/// LZ77 factor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Factor<T> {
+    /// Single character, which does not occur before in the LZ77 parsing, or is not worth a copy in [`lzss`].
+    Literal(T),
+    /// Copy of `text[src..src+len]`, which may overlap with the factor itself, i.e., `src + len` may exceed its position.
+    Copy { src: usize, len: usize },
+}
+
+impl<T> Factor<T> {
+    /// Returns the length of the string represented by the factor.
+    pub fn len(&self) -> usize {
+        match self {
+            Factor::Literal(_) => 1,
+            Factor::Copy { len, .. } => *len,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Errors of LZ77 decoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The `index`-th factor copies from `src` at position `pos` of the text,
+    /// but the source must begin before `pos` and the length must be positive.
+    InvalidCopy {
+        index: usize,
+        pos: usize,
+        src: usize,
+        len: usize,
+    },
+    /// The `index`-th factor at position `pos` copies `len` characters, which cannot be allocated.
+    TooLong {
+        index: usize,
+        pos: usize,
+        len: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidCopy {
+                index,
+                pos,
+                src,
+                len,
+            } => write!(
+                f,
+                "factor {} at position {} has an invalid copy of length {} from {}",
+                index, pos, len, src
+            ),
+            DecodeError::TooLong { index, pos, len } => write!(
+                f,
+                "factor {} at position {} has a copy of length {} exceeding the memory",
+                index, pos, len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes LZ77 factors into the text.
+///
+/// Characters of a copy are copied one by one, so the copy can refer to itself,
+/// e.g., `[Literal(a), Copy { src: 0, len: 5 }]` represents `aaaaaa`.
+pub fn decode<T>(factors: &[Factor<T>]) -> Result<Vec<T>, DecodeError>
+where
+    T: Clone,
+{
+    let mut text = vec![];
+    for (index, factor) in factors.iter().enumerate() {
+        decode_factor(&mut text, index, factor)?;
+    }
+    Ok(text)
+}
+
+/// Appends the string represented by the `index`-th factor to the text decoded so far.
+///
+/// The length of a copy is not trusted: the text grows only after the memory is allocated successfully.
+pub fn decode_factor<T>(
+    text: &mut Vec<T>,
+    index: usize,
+    factor: &Factor<T>,
+) -> Result<(), DecodeError>
+where
+    T: Clone,
+{
+    match *factor {
+        Factor::Literal(ref c) => text.push(c.clone()),
+        Factor::Copy { src, len } => {
+            let pos = text.len();
+            if src >= pos || len == 0 {
+                return Err(DecodeError::InvalidCopy {
+                    index,
+                    pos,
+                    src,
+                    len,
+                });
+            }
+            text.try_reserve(len)
+                .map_err(|_| DecodeError::TooLong { index, pos, len })?;
+            for k in 0..len {
+                text.push(text[src + k].clone());
+            }
+        }
+    }
+    Ok(())
+}
 
 /// Make previous/next smaller value arrays.
 pub fn make_psv_nsv_texta(sa: &[usize]) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
@@ -10,24 +129,26 @@ pub fn make_psv_nsv_texta(sa: &[usize]) -> (Vec<Option<usize>>, Vec<Option<usize
 
     let mut previ = None;
     for i in 0..n {
-        while previ.is_some() && previ.unwrap() > sa[i] as usize {
+        while previ.is_some() && previ.unwrap() > sa[i] {
             let pi = previ.unwrap();
-            nsva[pi] = Some(sa[i] as usize);
+            nsva[pi] = Some(sa[i]);
             previ = psva[pi];
         }
-        psva[sa[i] as usize] = previ;
-        previ = Some(sa[i] as usize);
+        psva[sa[i]] = previ;
+        previ = Some(sa[i]);
     }
     (psva, nsva)
 }
 
 /// Compute longest extention of text at positions `i` and `j`.
-fn longest_extention(text: &[u8], i: usize, j: usize) -> usize {
+fn longest_extention<T>(text: &[T], i: usize, j: usize) -> usize
+where
+    T: PartialEq,
+{
     let mut match_len = 0;
     while i + match_len < text.len() && j + match_len < text.len() {
         if text[i + match_len] != text[j + match_len] {
             break;
-            // return match_len;
         }
         match_len += 1;
     }
@@ -37,29 +158,28 @@ fn longest_extention(text: &[u8], i: usize, j: usize) -> usize {
 /// Compute a longest previous factor of `text[i..]` that starts at j.
 /// Returns a pair `(prev_len, prev_occ)`, where
 /// `prev_len`: the length of the factor
-/// `prev_occ`: If `prev_len==0`, it represents a chracter `text[i]`. Otherwise, it represents the previous occurrence that `text[i..i+prev_len]==text[prev_occ..prev_occ+prev_len]`.
-fn calc_factor(text: &[u8], i: usize, j: Option<usize>) -> (usize, usize) {
+/// `prev_occ`: If `prev_len==0`, it is `i`, i.e., the factor is a character `text[i]`. Otherwise, it represents the previous occurrence that `text[i..i+prev_len]==text[prev_occ..prev_occ+prev_len]`.
+fn calc_factor<T>(text: &[T], i: usize, j: Option<usize>) -> (usize, usize)
+where
+    T: PartialEq,
+{
     let plen = match j {
         Some(pos) => longest_extention(text, i, pos),
         None => 0,
     };
-    let pocc = if plen == 0 {
-        text[i] as usize
-    } else {
-        j.unwrap()
-    };
+    let pocc = if plen == 0 { i } else { j.unwrap() };
     (plen, pocc)
 }
 
-/// Compute LZ77 factors.
+/// Compute LZ77 factors of a text over an integer alphabet, e.g., bytes, or `u16`/`u32` tokens.
 /// Returns pairs `[(prev_len, prev_occ), ...]`, where
 /// `prev_len`: the length of the factor
-/// `prev_occ`: If `prev_len==0`, it represents a chracter `text[i]`, where `i` is a position of the factor in the text. Otherwise, it represents the previous occurrence that `text[i..i+prev_len]==text[prev_occ..prev_occ+prev_len]`.
-pub fn lz77(text: &[u8]) -> Vec<(usize, usize)> {
-    let sa: Vec<usize> = {
-        let sa32 = make_sa_induce(text);
-        sa32.into_iter().map(|x| x as usize).collect()
-    };
+/// `prev_occ`: If `prev_len==0`, it is the position `i` of the factor in the text, i.e., the factor is a character `text[i]`. Otherwise, it represents the previous occurrence that `text[i..i+prev_len]==text[prev_occ..prev_occ+prev_len]`.
+pub fn lz77<T>(text: &[T]) -> Vec<(usize, usize)>
+where
+    T: Ord + IntoUsize,
+{
+    let sa: Vec<usize> = make_sa_induce(text);
     let (psva, nsva) = make_psv_nsv_texta(&sa);
     let mut i = 0;
     let n = text.len();
@@ -74,18 +194,118 @@ pub fn lz77(text: &[u8]) -> Vec<(usize, usize)> {
     res
 }
 
+/// Compute LZ77 factors as [`Factor`]s.
+pub fn lz77_factors<T>(text: &[T]) -> Vec<Factor<T>>
+where
+    T: Ord + IntoUsize + Clone,
+{
+    lz77(text)
+        .into_iter()
+        .map(|(prev_len, prev_occ)| match prev_len {
+            0 => Factor::Literal(text[prev_occ].clone()),
+            len => Factor::Copy { src: prev_occ, len },
+        })
+        .collect()
+}
+
 #[test]
 fn test_lz77() {
     let text = br"ab";
-    let res = vec![(0, text[0] as usize), (0, text[1] as usize)];
+    let res = vec![(0, 0), (0, 1)];
     assert_eq!(res, lz77(text));
-    assert_eq!(lz77(br"aaaaaa"), vec![(0, br"a"[0] as usize), (5, 0)]);
+    assert_eq!(lz77(br"aaaaaa"), vec![(0, 0), (5, 0)]);
+    assert_eq!(lz77(br"baaaaaa"), vec![(0, 0), (0, 1), (5, 1)]);
+    assert_eq!(lz77(br"abababab"), vec![(0, 0), (0, 1), (6, 0)]);
+}
+
+#[test]
+fn test_lz77_generic() {
+    use crate::commons::enum_strs_len_leq;
+
+    // Longest previous factor lengths computed naively.
+    fn lz77_lens<T: PartialEq>(text: &[T]) -> Vec<usize> {
+        let mut res = vec![];
+        let mut i = 0;
+        while i < text.len() {
+            let len = (0..i)
+                .map(|j| longest_extention(text, i, j))
+                .max()
+                .unwrap_or(0);
+            res.push(len);
+            i += max(1, len);
+        }
+        res
+    }
+
+    let text: Vec<u32> = vec![70000, 1, 70000, 1, 70000, 2, 1 << 31, 2, 1 << 31];
+    let factors = lz77_factors(&text);
+    assert_eq!(
+        factors,
+        vec![
+            Factor::Literal(70000),
+            Factor::Literal(1),
+            Factor::Copy { src: 0, len: 3 },
+            Factor::Literal(2),
+            Factor::Literal(1 << 31),
+            Factor::Copy { src: 5, len: 2 },
+        ]
+    );
+    assert_eq!(decode(&factors).unwrap(), text);
+
+    for text in enum_strs_len_leq(&[0u16, 300, 65535], 7) {
+        let res = lz77(&text);
+        let lens: Vec<usize> = res.iter().map(|&(len, _)| len).collect();
+        assert_eq!(lens, lz77_lens(&text));
+        for (i, &(len, occ)) in res.iter().enumerate() {
+            if len == 0 {
+                let pos: usize = res[..i].iter().map(|&(len, _)| max(1, len)).sum();
+                assert_eq!(occ, pos);
+            }
+        }
+        assert_eq!(decode(&lz77_factors(&text)).unwrap(), text);
+    }
+}
+
+#[test]
+fn test_lz77_decode() {
+    use crate::commons::test_texts;
+
+    let factors = lz77_factors(br"abababab");
+    assert_eq!(
+        factors,
+        vec![
+            Factor::Literal(b'a'),
+            Factor::Literal(b'b'),
+            Factor::Copy { src: 0, len: 6 }
+        ]
+    );
+    assert_eq!(decode(&factors).unwrap(), br"abababab".to_vec());
+    assert!(decode::<u8>(&[]).unwrap().is_empty());
     assert_eq!(
-        lz77(br"baaaaaa"),
-        vec![(0, br"b"[0] as usize), (0, br"a"[0] as usize), (5, 1)]
+        decode(&[Factor::Literal(b'a'), Factor::Copy { src: 1, len: 2 }]),
+        Err(DecodeError::InvalidCopy {
+            index: 1,
+            pos: 1,
+            src: 1,
+            len: 2
+        })
     );
+    let huge = Factor::Copy {
+        src: 0,
+        len: usize::MAX,
+    };
     assert_eq!(
-        lz77(br"abababab"),
-        vec![(0, br"a"[0] as usize), (0, br"b"[0] as usize), (6, 0)]
+        decode(&[Factor::Literal(b'a'), huge, huge]),
+        Err(DecodeError::TooLong {
+            index: 1,
+            pos: 1,
+            len: usize::MAX
+        })
     );
+
+    for text in test_texts() {
+        let factors = lz77_factors(&text);
+        assert_eq!(factors.len(), lz77(&text).len());
+        assert_eq!(decode(&factors).unwrap(), text);
+    }
 }
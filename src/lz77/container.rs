@@ -0,0 +1,304 @@
+//! Binary container of LZ77 factors.
+//!
+//! The format consists of a header and factors, where all the integers are encoded by LEB128 varints.
+//! The header is the magic bytes [`MAGIC`], a byte of [`VERSION`], and the length of the text.
+//! A literal is encoded by `0` followed by its byte,
+//! and a copy `text[src..src+len]` at position `pos` by `len` followed by the distance `pos - src`.
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use super::{decode_factor, lz77_factors, Factor};
+
+pub const MAGIC: [u8; 4] = *b"LZ77";
+pub const VERSION: u8 = 1;
+
+/// Writes an integer as a LEB128 varint, i.e., 7 bits per byte from the least significant ones,
+/// where the highest bit of a byte tells whether more bytes follow.
+pub fn write_varint<W: Write>(w: &mut W, mut x: u64) -> io::Result<usize> {
+    let mut buf = [0u8; 10];
+    let mut len = 0;
+    loop {
+        let byte = (x & 0x7f) as u8;
+        x >>= 7;
+        if x == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    w.write_all(&buf[..len])?;
+    Ok(len)
+}
+
+/// Reads an integer encoded by [`write_varint`].
+pub fn read_varint<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut x = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        r.read_exact(&mut byte)?;
+        let bits = u64::from(byte[0] & 0x7f);
+        if shift == 63 && bits > 1 {
+            break;
+        }
+        x |= bits << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(x);
+        }
+    }
+    Err(invalid_data("varint overflows 64 bits"))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn to_usize(x: u64) -> io::Result<usize> {
+    usize::try_from(x).map_err(|_| invalid_data("integer overflows usize"))
+}
+
+/// Streaming encoder of LZ77 factors.
+pub struct Encoder<W> {
+    w: W,
+    /// Length of the text.
+    len: usize,
+    /// Position of the next factor.
+    pos: usize,
+    /// Number of bytes written.
+    written: usize,
+}
+
+impl<W: Write> Encoder<W> {
+    /// Creates an encoder of a text of length `len` and writes the header.
+    pub fn new(mut w: W, len: usize) -> io::Result<Self> {
+        w.write_all(&MAGIC)?;
+        w.write_all(&[VERSION])?;
+        let written = MAGIC.len() + 1 + write_varint(&mut w, len as u64)?;
+        Ok(Self {
+            w,
+            len,
+            pos: 0,
+            written,
+        })
+    }
+
+    /// Writes a factor following the previous ones.
+    pub fn write_factor(&mut self, factor: &Factor<u8>) -> io::Result<()> {
+        if factor.len() > self.len - self.pos {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "factors exceed the length of the text",
+            ));
+        }
+        match *factor {
+            Factor::Literal(c) => {
+                self.written += write_varint(&mut self.w, 0)?;
+                self.w.write_all(&[c])?;
+                self.written += 1;
+            }
+            Factor::Copy { src, len } => {
+                if src >= self.pos || len == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "a copy must have a positive length and a preceding source",
+                    ));
+                }
+                self.written += write_varint(&mut self.w, len as u64)?;
+                self.written += write_varint(&mut self.w, (self.pos - src) as u64)?;
+            }
+        }
+        self.pos += factor.len();
+        Ok(())
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Checks that the factors cover the text, and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.pos != self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "factors do not cover the text",
+            ));
+        }
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
+
+/// Streaming decoder of LZ77 factors, which iterates over the factors.
+pub struct Decoder<R> {
+    r: R,
+    /// Length of the text.
+    len: usize,
+    /// Position of the next factor.
+    pos: usize,
+}
+
+impl<R: Read> Decoder<R> {
+    /// Creates a decoder reading the header.
+    pub fn new(mut r: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid_data("invalid magic bytes"));
+        }
+        let mut version = [0u8];
+        r.read_exact(&mut version)?;
+        if version[0] != VERSION {
+            return Err(invalid_data("unsupported version"));
+        }
+        let len = to_usize(read_varint(&mut r)?)?;
+        Ok(Self { r, len, pos: 0 })
+    }
+
+    /// Returns the length of the text.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads the next factor, or returns `None` if all the factors have been read.
+    pub fn read_factor(&mut self) -> io::Result<Option<Factor<u8>>> {
+        if self.pos == self.len {
+            return Ok(None);
+        }
+        let factor = match to_usize(read_varint(&mut self.r)?)? {
+            0 => {
+                let mut c = [0u8];
+                self.r.read_exact(&mut c)?;
+                Factor::Literal(c[0])
+            }
+            len => {
+                let dist = to_usize(read_varint(&mut self.r)?)?;
+                if dist == 0 || dist > self.pos {
+                    return Err(invalid_data("copy from outside of the text"));
+                }
+                Factor::Copy {
+                    src: self.pos - dist,
+                    len,
+                }
+            }
+        };
+        if factor.len() > self.len - self.pos {
+            return Err(invalid_data("factors exceed the length of the text"));
+        }
+        self.pos += factor.len();
+        Ok(Some(factor))
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = io::Result<Factor<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_factor().transpose()
+    }
+}
+
+/// Compresses a text by [`crate::lz77::lz77`], and returns the number of bytes written.
+pub fn compress<W: Write>(text: &[u8], w: W) -> io::Result<usize> {
+    let mut encoder = Encoder::new(w, text.len())?;
+    for factor in lz77_factors(text) {
+        encoder.write_factor(&factor)?;
+    }
+    let written = encoder.written();
+    encoder.finish()?;
+    Ok(written)
+}
+
+/// Decompresses a text written by [`compress`].
+///
+/// The text is decoded factor by factor without trusting the length in the header.
+pub fn decompress<R: Read>(r: R) -> io::Result<Vec<u8>> {
+    let mut text = vec![];
+    for (index, factor) in Decoder::new(r)?.enumerate() {
+        decode_factor(&mut text, index, &factor?).map_err(|e| invalid_data(&e.to_string()))?;
+    }
+    Ok(text)
+}
+
+#[test]
+fn test_varint() {
+    let xs = [0, 1, 127, 128, 300, 1 << 35, u64::MAX];
+    let mut buf = vec![];
+    let lens: Vec<usize> = xs
+        .iter()
+        .map(|&x| write_varint(&mut buf, x).unwrap())
+        .collect();
+    assert_eq!(lens, vec![1, 1, 1, 2, 2, 6, 10]);
+    assert_eq!(&buf[3..5], &[0x80, 0x01]);
+    let mut r = &buf[..];
+    for &x in xs.iter() {
+        assert_eq!(read_varint(&mut r).unwrap(), x);
+    }
+    assert!(read_varint(&mut r).is_err());
+    let mut r: &[u8] = &[0xff; 10];
+    assert_eq!(
+        read_varint(&mut r).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn test_container() {
+    use crate::commons::enum_strs_len_leq;
+    use crate::fib::fibstr_ab;
+
+    let mut buf = vec![];
+    let written = compress(br"abababab", &mut buf).unwrap();
+    assert_eq!(written, buf.len());
+    // Header, two literals, and a copy of length 6 from distance 2.
+    assert_eq!(buf, [&b"LZ77\x01\x08"[..], b"\x00a\x00b\x06\x02"].concat());
+    let factors: Vec<Factor<u8>> = Decoder::new(&buf[..])
+        .unwrap()
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(factors, lz77_factors(br"abababab"));
+
+    let mut texts = vec![br"mississippi".to_vec(), fibstr_ab(20), vec![0xff; 1000]];
+    texts.extend(enum_strs_len_leq(br"abc", 5));
+    for text in texts {
+        let mut buf = vec![];
+        compress(&text, &mut buf).unwrap();
+        assert_eq!(decompress(&buf[..]).unwrap(), text);
+    }
+
+    // Broken inputs.
+    assert!(decompress(&b"LZ78\x01\x00"[..]).is_err());
+    assert!(decompress(&b"LZ77\x02\x00"[..]).is_err());
+    assert!(decompress(&b"LZ77\x01\x03\x00a\x02\x02"[..]).is_err());
+    assert!(decompress(&b"LZ77\x01\x03\x00a\x03\x01"[..]).is_err());
+    assert!(decompress(&b"LZ77\x01\x03\x00a"[..]).is_err());
+    // A few bytes declaring a text of length 2^60 by a single copy.
+    let mut bomb = b"LZ77\x01".to_vec();
+    write_varint(&mut bomb, 1 << 60).unwrap();
+    bomb.extend(b"\x00a");
+    write_varint(&mut bomb, (1 << 60) - 1).unwrap();
+    write_varint(&mut bomb, 1).unwrap();
+    assert_eq!(
+        decompress(&bomb[..]).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    let mut encoder = Encoder::new(vec![], 2).unwrap();
+    encoder.write_factor(&Factor::Literal(b'a')).unwrap();
+    assert!(encoder
+        .write_factor(&Factor::Copy { src: 0, len: 2 })
+        .is_err());
+    assert!(encoder
+        .write_factor(&Factor::Copy {
+            src: 0,
+            len: usize::MAX
+        })
+        .is_err());
+    assert!(encoder.finish().is_err());
+}
@@ -0,0 +1,415 @@
+//! Sliding-window LZ77 / LZSS
+//!
+//! Practical LZ77 compressors search sources only in a bounded window preceding the current position,
+//! and bound the lengths of copies.
+//! LZSS (Storer and Szymanski, 1982) emits a literal instead of a copy shorter than a threshold.
+//! With lazy matching, as in gzip, a literal is emitted instead of a copy
+//! if a longer copy begins at the next position.
+//!
+//! [`LzssEncoder`] reads a text from a [`Read`] and keeps only the window, the lookahead
+//! and a hash table of a fixed size in memory.
+//! Candidate sources are chained by a rolling hash of their first `min_len` bytes as in gzip,
+//! and at most `max_chain` candidates in the window are examined for each copy.
+//! Without the limit, every copy is the longest one within the window,
+//! but it takes $O(n \cdot w)$ time in the worst case for a window of size $w$.
+
+use std::io::{self, Read};
+
+use super::Factor;
+
+/// Parameters of the sliding-window parsing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LzssConfig {
+    /// Maximum distance from a copy to its source.
+    pub window: usize,
+    /// Maximum length of a copy.
+    pub max_len: usize,
+    /// Minimum length of a copy, where shorter matches are emitted as literals.
+    pub min_len: usize,
+    /// Whether to emit a literal if a longer copy begins at the next position.
+    pub lazy: bool,
+    /// Maximum number of candidate sources examined for a copy.
+    pub max_chain: usize,
+}
+
+impl Default for LzssConfig {
+    /// Returns the parameters of DEFLATE, with the chain limit of the best compression level of gzip.
+    fn default() -> Self {
+        Self {
+            window: 1 << 15,
+            max_len: 258,
+            min_len: 3,
+            lazy: false,
+            max_chain: 4096,
+        }
+    }
+}
+
+/// Size of a chunk read from the underlying reader at once.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Maximum number of bits of the hash values, i.e., the hash table has at most `1 << MAX_HASH_BITS` entries.
+const MAX_HASH_BITS: u32 = 16;
+
+/// Base of the rolling hash.
+const HASH_BASE: u64 = 0x100000001b3;
+
+/// Streaming sliding-window encoder, which iterates over the factors of a text read from `R`.
+pub struct LzssEncoder<R> {
+    r: R,
+    config: LzssConfig,
+    /// Scratch buffer to read a chunk.
+    chunk: Box<[u8]>,
+    /// Bytes from position `base` of the text, which cover the window and the lookahead.
+    buf: Vec<u8>,
+    /// `prev[p - base]` is the previous position whose first `min_len` bytes have the same hash as position `p`.
+    prev: Vec<Option<usize>>,
+    /// Last position for each hash, where the table has as many entries as the window up to `1 << MAX_HASH_BITS`.
+    head: Vec<Option<usize>>,
+    /// Number of bits of the hash values.
+    hash_bits: u32,
+    /// `HASH_BASE` to the power of `min_len - 1`.
+    hash_pow: u64,
+    /// Position and rolling hash value of its first `min_len` bytes computed last.
+    rolling: Option<(usize, u64)>,
+    /// Position and its match computed for lazy matching, which is used for the next factor.
+    lookahead: Option<(usize, (usize, usize))>,
+    base: usize,
+    /// Position of the next factor.
+    pos: usize,
+    /// Positions before it are registered in the hash chains.
+    inserted: usize,
+    eof: bool,
+}
+
+impl<R: Read> LzssEncoder<R> {
+    pub fn new(r: R, config: LzssConfig) -> Self {
+        assert!(config.window > 0, "the window must not be empty");
+        assert!(
+            0 < config.min_len && config.min_len <= config.max_len,
+            "lengths of copies must satisfy 0 < min_len <= max_len"
+        );
+        let hash_bits = config
+            .window
+            .next_power_of_two()
+            .trailing_zeros()
+            .clamp(1, MAX_HASH_BITS);
+        let hash_pow = (1..config.min_len).fold(1u64, |x, _| x.wrapping_mul(HASH_BASE));
+        Self {
+            r,
+            config,
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            buf: vec![],
+            prev: vec![],
+            head: vec![None; 1 << hash_bits],
+            hash_bits,
+            hash_pow,
+            rolling: None,
+            lookahead: None,
+            base: 0,
+            pos: 0,
+            inserted: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns the end position of the bytes read so far.
+    fn end(&self) -> usize {
+        self.base + self.buf.len()
+    }
+
+    /// Reads the text until position `end` or the end of the text.
+    fn fill(&mut self, end: usize) -> io::Result<()> {
+        while !self.eof && self.end() < end {
+            match self.r.read(&mut self.chunk) {
+                Ok(0) => self.eof = true,
+                Ok(len) => {
+                    self.buf.extend_from_slice(&self.chunk[..len]);
+                    self.prev.resize(self.buf.len(), None);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the bytes out of the window.
+    fn compact(&mut self) {
+        let keep = self.pos.saturating_sub(self.config.window);
+        if keep - self.base > self.config.window.max(CHUNK_SIZE) {
+            self.buf.drain(..keep - self.base);
+            self.prev.drain(..keep - self.base);
+            self.base = keep;
+        }
+    }
+
+    /// Returns the entry of the hash table for the first `min_len` bytes at position `p`, if they exist.
+    ///
+    /// The hash value is rolled from the one of position `p-1` in $O(1)$ time if it has been computed last.
+    fn key(&mut self, p: usize) -> Option<usize> {
+        let beg = p - self.base;
+        let bytes = self.buf.get(beg..beg + self.config.min_len)?;
+        let hash = match self.rolling {
+            Some((q, hash)) if q == p => hash,
+            Some((q, hash)) if q + 1 == p => {
+                let out = u64::from(self.buf[beg - 1]).wrapping_mul(self.hash_pow);
+                let last = u64::from(bytes[bytes.len() - 1]);
+                hash.wrapping_sub(out)
+                    .wrapping_mul(HASH_BASE)
+                    .wrapping_add(last)
+            }
+            _ => bytes.iter().fold(0u64, |hash, &c| {
+                hash.wrapping_mul(HASH_BASE).wrapping_add(u64::from(c))
+            }),
+        };
+        self.rolling = Some((p, hash));
+        // Multiplicative hashing takes the highest bits.
+        Some((hash.wrapping_mul(0x9e3779b97f4a7c15) >> (64 - self.hash_bits)) as usize)
+    }
+
+    /// Registers the positions before `p` in the hash chains.
+    fn insert_until(&mut self, p: usize) {
+        while self.inserted < p {
+            let q = self.inserted;
+            if let Some(key) = self.key(q) {
+                self.prev[q - self.base] = self.head[key].replace(q);
+            }
+            self.inserted += 1;
+        }
+    }
+
+    /// Returns the closest longest source and its length for a copy at position `p`,
+    /// where the length is `0` if there are no copies of length at least `min_len`.
+    fn longest_match(&mut self, p: usize) -> io::Result<(usize, usize)> {
+        self.fill(p + self.config.max_len)?;
+        self.insert_until(p);
+        let limit = self.config.max_len.min(self.end() - p);
+        let mut best = (0, 0);
+        if limit < self.config.min_len {
+            return Ok(best);
+        }
+        let lower = p.saturating_sub(self.config.window);
+        let mut cand = self.key(p).and_then(|key| self.head[key]);
+        let mut chain = 0;
+        while let Some(src) = cand.filter(|&src| src >= lower && chain < self.config.max_chain) {
+            chain += 1;
+            let len = (0..limit)
+                .take_while(|&k| self.buf[src + k - self.base] == self.buf[p + k - self.base])
+                .count();
+            if len > best.1 {
+                best = (src, len);
+                if len == limit {
+                    break;
+                }
+            }
+            cand = self.prev[src - self.base];
+        }
+        if best.1 < self.config.min_len {
+            best = (0, 0);
+        }
+        Ok(best)
+    }
+
+    /// Computes the next factor, or returns `None` at the end of the text.
+    pub fn next_factor(&mut self) -> io::Result<Option<Factor<u8>>> {
+        self.fill(self.pos + 1)?;
+        if self.pos == self.end() {
+            return Ok(None);
+        }
+        let (src, len) = match self.lookahead.take() {
+            Some((p, best)) if p == self.pos => best,
+            _ => self.longest_match(self.pos)?,
+        };
+        let lazy = if self.config.lazy && len > 0 && len < self.config.max_len {
+            let next = self.longest_match(self.pos + 1)?;
+            self.lookahead = Some((self.pos + 1, next));
+            next.1 > len
+        } else {
+            false
+        };
+        let factor = if len == 0 || lazy {
+            Factor::Literal(self.buf[self.pos - self.base])
+        } else {
+            Factor::Copy { src, len }
+        };
+        self.pos += factor.len();
+        self.compact();
+        Ok(Some(factor))
+    }
+}
+
+impl<R: Read> Iterator for LzssEncoder<R> {
+    type Item = io::Result<Factor<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_factor().transpose()
+    }
+}
+
+/// Computes the sliding-window parsing of a text in memory.
+pub fn lzss(text: &[u8], config: LzssConfig) -> Vec<Factor<u8>> {
+    LzssEncoder::new(text, config)
+        .collect::<io::Result<_>>()
+        .expect("reading a slice never fails")
+}
+
+#[cfg(test)]
+fn check_factors(text: &[u8], factors: &[Factor<u8>], config: &LzssConfig) {
+    assert_eq!(super::decode(factors).unwrap(), text);
+    let mut pos = 0;
+    for factor in factors {
+        if let Factor::Copy { src, len } = *factor {
+            assert!(pos - src <= config.window);
+            assert!(config.min_len <= len && len <= config.max_len);
+        }
+        pos += factor.len();
+    }
+}
+
+#[test]
+fn test_lzss() {
+    let config = LzssConfig {
+        window: 1 << 10,
+        ..Default::default()
+    };
+    let text = br"abcXbcdeYabcde";
+    let lit = |c: u8| Factor::Literal(c);
+    let copy = |src, len| Factor::Copy { src, len };
+    let head: Vec<Factor<u8>> = text[..9].iter().map(|&c| lit(c)).collect();
+    let greedy = lzss(text, config);
+    assert_eq!(
+        greedy,
+        [&head[..], &[copy(0, 3), lit(b'd'), lit(b'e')]].concat()
+    );
+    let lazy = lzss(
+        text,
+        LzssConfig {
+            lazy: true,
+            ..config
+        },
+    );
+    assert_eq!(lazy, [&head[..], &[lit(b'a'), copy(4, 4)]].concat());
+
+    // The window and the maximum length bound copies.
+    let config = LzssConfig {
+        window: 2,
+        max_len: 4,
+        min_len: 1,
+        lazy: false,
+        max_chain: usize::MAX,
+    };
+    assert_eq!(
+        lzss(br"abababab", config),
+        vec![lit(b'a'), lit(b'b'), copy(0, 4), copy(4, 2)]
+    );
+    assert_eq!(lzss(br"abcabc", config), br"abcabc".map(lit).to_vec());
+    assert!(lzss(br"", config).is_empty());
+
+    // The chain limit stops at the closest candidates.
+    let text = br"abcdXabcYabcd";
+    let mut head: Vec<Factor<u8>> = text[..5].iter().map(|&c| lit(c)).collect();
+    head.extend([copy(0, 3), lit(b'Y')]);
+    let config = LzssConfig::default();
+    assert_eq!(lzss(text, config), [&head[..], &[copy(0, 4)]].concat());
+    let config = LzssConfig {
+        max_chain: 1,
+        ..config
+    };
+    assert_eq!(
+        lzss(text, config),
+        [&head[..], &[copy(5, 3), lit(b'd')]].concat()
+    );
+}
+
+#[test]
+fn test_lzss_naive() {
+    use super::lz77;
+    use crate::commons::test_texts;
+
+    // A long run makes long hash chains.
+    let mut texts = test_texts();
+    texts.push(vec![b'a'; 1000]);
+    for text in texts.iter() {
+        // Without bounds, it is the greedy LZ77 parsing.
+        let config = LzssConfig {
+            window: text.len().max(1),
+            max_len: text.len().max(1),
+            min_len: 1,
+            lazy: false,
+            max_chain: usize::MAX,
+        };
+        let lens: Vec<usize> = lzss(text, config).iter().map(|f| f.len()).collect();
+        let ans: Vec<usize> = lz77(text).iter().map(|&(len, _)| len.max(1)).collect();
+        assert_eq!(lens, ans);
+
+        for &(window, max_len, min_len) in &[(1, 1, 1), (3, 5, 1), (4, 8, 2), (16, 4, 3)] {
+            for lazy in [false, true] {
+                let config = LzssConfig {
+                    window,
+                    max_len,
+                    min_len,
+                    lazy,
+                    max_chain: usize::MAX,
+                };
+                let factors = lzss(text, config);
+                check_factors(text, &factors, &config);
+                if lazy {
+                    continue;
+                }
+                // Every copy is the longest one within the window.
+                let mut pos = 0;
+                for factor in factors {
+                    let ext = |src: usize| {
+                        (0..max_len.min(text.len() - pos))
+                            .take_while(|&k| text[src + k] == text[pos + k])
+                            .count()
+                    };
+                    let best = (pos.saturating_sub(window)..pos)
+                        .map(ext)
+                        .max()
+                        .unwrap_or(0);
+                    let best = if best < min_len { 0 } else { best };
+                    assert_eq!(factor.len(), best.max(1));
+                    pos += factor.len();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lzss_stream() {
+    use crate::fib::fibstr_ab;
+
+    /// Reader returning a few bytes at a time.
+    struct Trickle<'a>(&'a [u8], usize);
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.1 = self.1 % 3 + 1;
+            let len = self.1.min(buf.len()).min(self.0.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    let mut text = fibstr_ab(25);
+    text.extend((0..100_000u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8));
+    for config in [
+        LzssConfig::default(),
+        LzssConfig {
+            window: 100,
+            max_len: 1000,
+            min_len: 2,
+            lazy: true,
+            max_chain: 8,
+        },
+    ] {
+        let factors: Vec<Factor<u8>> = LzssEncoder::new(Trickle(&text, 0), config)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(factors, lzss(&text, config));
+        check_factors(&text, &factors, &config);
+    }
+}
@@ -0,0 +1,428 @@
+//! Variants of LZ77 parsing.
+//!
+//! - [`lz77_with`] computes the LZ77 parsing choosing sources by a [`SourcePolicy`],
+//!   optionally forbidding sources to overlap with their factors.
+//! - [`lz_end`] computes the LZ-End parsing (Kreft and Navarro, 2010),
+//!   where the copy of every phrase ends at the end of a previous phrase.
+//!
+//! Factors of the LZ77 variants are extended character by character on the suffix array,
+//! narrowing the interval of suffixes that begin with the factor in $O(\log n)$ time per character.
+
+use std::{cmp::max, fmt, ops::Range, str::FromStr};
+
+use super::Factor;
+use crate::commons::failure_function;
+use crate::suffix_array::sais::{make_sa_induce, IntoUsize};
+
+/// Policy to choose the source of a copy among its previous occurrences.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SourcePolicy {
+    /// The leftmost occurrence.
+    Leftmost,
+    /// The rightmost occurrence, which minimizes the distance to the factor.
+    Rightmost,
+    /// The occurrence whose suffix is lexicographically closest to the one of the factor,
+    /// preferring the larger position if the closest ones on both sides are occurrences,
+    /// as [`super::lz77`] chooses in $O(1)$ time via PSV/NSV arrays.
+    #[default]
+    Closest,
+}
+
+impl SourcePolicy {
+    pub const ALL: [SourcePolicy; 3] = [
+        SourcePolicy::Leftmost,
+        SourcePolicy::Rightmost,
+        SourcePolicy::Closest,
+    ];
+
+    /// Returns the name of the policy.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourcePolicy::Leftmost => "leftmost",
+            SourcePolicy::Rightmost => "rightmost",
+            SourcePolicy::Closest => "closest",
+        }
+    }
+}
+
+impl fmt::Display for SourcePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SourcePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SourcePolicy::ALL
+            .iter()
+            .copied()
+            .find(|policy| policy.name() == s)
+            .ok_or_else(|| format!("unknown source policy: {}", s))
+    }
+}
+
+/// Sparse table answering range minimum queries in $O(1)$ time.
+struct RangeMin {
+    /// `table[k][i]` is the minimum of `values[i..i+2^k]`.
+    table: Vec<Vec<usize>>,
+}
+
+impl RangeMin {
+    fn new(values: &[usize]) -> Self {
+        let mut table = vec![values.to_vec()];
+        let mut k = 1;
+        while 2 * k <= values.len() {
+            let prev = table.last().unwrap();
+            let row = (0..prev.len() - k)
+                .map(|i| prev[i].min(prev[i + k]))
+                .collect();
+            table.push(row);
+            k *= 2;
+        }
+        Self { table }
+    }
+
+    /// Returns the minimum of a non-empty range.
+    fn min(&self, range: Range<usize>) -> usize {
+        let k = (usize::BITS - 1 - range.len().leading_zeros()) as usize;
+        self.table[k][range.start].min(self.table[k][range.end - (1 << k)])
+    }
+}
+
+/// Suffix array of a text with the range minimum queries of positions.
+struct Index<'a, T> {
+    text: &'a [T],
+    sa: Vec<usize>,
+    rank: Vec<usize>,
+    min_pos: RangeMin,
+}
+
+impl<'a, T> Index<'a, T>
+where
+    T: Ord + IntoUsize,
+{
+    fn new(text: &'a [T]) -> Self {
+        let sa: Vec<usize> = make_sa_induce(text);
+        let mut rank = vec![0; text.len()];
+        sa.iter().enumerate().for_each(|(r, &i)| rank[i] = r);
+        let min_pos = RangeMin::new(&sa);
+        Self {
+            text,
+            sa,
+            rank,
+            min_pos,
+        }
+    }
+
+    /// Narrows the range of suffixes beginning with a string of length `depth`
+    /// to the ones followed by `c`.
+    fn narrow(&self, range: Range<usize>, depth: usize, c: &T) -> Range<usize> {
+        let sa_range = &self.sa[range.clone()];
+        let beg = sa_range.partition_point(|&i| self.text.get(i + depth) < Some(c));
+        let end = sa_range.partition_point(|&i| self.text.get(i + depth) <= Some(c));
+        range.start + beg..range.start + end
+    }
+
+    /// Computes the longest factor at position `i` and its source.
+    fn factor(&self, i: usize, overlap: bool, policy: SourcePolicy) -> Factor<T>
+    where
+        T: Clone,
+    {
+        let n = self.text.len();
+        // A source `p` of length `len` is valid if `p < i`, and `p + len <= i` without overlaps.
+        let valid = |p: usize, len: usize| if overlap { p < i } else { p + len <= i };
+        let mut range = 0..n;
+        let mut len = 0;
+        while i + len < n {
+            let next = self.narrow(range.clone(), len, &self.text[i + len]);
+            if !valid(self.min_pos.min(next.clone()), len + 1) {
+                break;
+            }
+            range = next;
+            len += 1;
+        }
+        if len == 0 {
+            return Factor::Literal(self.text[i].clone());
+        }
+        let src = match policy {
+            SourcePolicy::Leftmost => self.min_pos.min(range),
+            SourcePolicy::Rightmost => self.sa[range]
+                .iter()
+                .copied()
+                .filter(|&p| valid(p, len))
+                .max()
+                .unwrap(),
+            SourcePolicy::Closest => {
+                let r = self.rank[i];
+                let prev = self.sa[range.start..r]
+                    .iter()
+                    .rev()
+                    .copied()
+                    .find(|&p| valid(p, len));
+                let next = self.sa[r + 1..range.end]
+                    .iter()
+                    .copied()
+                    .find(|&p| valid(p, len));
+                max(prev, next).unwrap()
+            }
+        };
+        Factor::Copy { src, len }
+    }
+}
+
+/// Computes the LZ77 parsing whose sources are chosen by `policy`,
+/// where sources may overlap with their factors if `overlap`.
+///
+/// It takes $O(n \log n)$ time besides choosing sources,
+/// which takes $O(1)$ time for [`SourcePolicy::Leftmost`] and $O(occ)$ time for the others,
+/// where $occ$ is the number of occurrences of the factor.
+pub fn lz77_with<T>(text: &[T], overlap: bool, policy: SourcePolicy) -> Vec<Factor<T>>
+where
+    T: Ord + IntoUsize + Clone,
+{
+    let index = Index::new(text);
+    let mut res = vec![];
+    let mut i = 0;
+    while i < text.len() {
+        let factor = index.factor(i, overlap, policy);
+        i += factor.len();
+        res.push(factor);
+    }
+    res
+}
+
+/// Computes the LZ77 parsing whose copies do not overlap with themselves,
+/// i.e., every factor occurs in the prefix of the text preceding it.
+pub fn lz77_non_overlapping<T>(text: &[T]) -> Vec<Factor<T>>
+where
+    T: Ord + IntoUsize + Clone,
+{
+    lz77_with(text, false, SourcePolicy::default())
+}
+
+/// Phrase of the LZ-End parsing, which is a copy followed by a character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LzEndPhrase<T> {
+    /// Index of the phrase at whose end the copy ends, which is meaningless if `len == 0`.
+    pub src: usize,
+    /// Length of the copy.
+    pub len: usize,
+    /// Character following the copy, which is `None` only if the copy reaches the end of the text.
+    pub next: Option<T>,
+}
+
+impl<T> LzEndPhrase<T> {
+    /// Returns the length of the string represented by the phrase.
+    pub fn len(&self) -> usize {
+        self.len + usize::from(self.next.is_some())
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Computes the LZ-End parsing.
+///
+/// Each phrase is the longest prefix of the rest of the text that is a suffix of the text ending at a phrase end,
+/// followed by the next character.
+/// It scans the preceding text by the KMP algorithm for every phrase, so it takes $O(nz)$ time for $z$ phrases.
+pub fn lz_end<T>(text: &[T]) -> Vec<LzEndPhrase<T>>
+where
+    T: Eq + Clone,
+{
+    let n = text.len();
+    let mut res = vec![];
+    // `ends[q]` is the ending position of phrase `q`.
+    let mut ends = vec![];
+    let mut i = 0;
+    while i < n {
+        // Copies end at or before `i`, so they are not longer than `i`.
+        let pattern = &text[i..n.min(2 * i)];
+        let fail = failure_function(pattern);
+        let mut best = (0, 0);
+        let mut q = 0;
+        let mut matched = 0;
+        for (e, c) in text[..i].iter().enumerate() {
+            while matched > 0 && (matched == pattern.len() || pattern[matched] != *c) {
+                matched = fail[matched - 1];
+            }
+            if pattern[matched] == *c {
+                matched += 1;
+            }
+            // `text[..e+1]` ends with `pattern[..matched]`.
+            if ends.get(q) == Some(&(e + 1)) {
+                best = max(best, (matched, q));
+                q += 1;
+            }
+        }
+        let (len, src) = best;
+        let next = text.get(i + len).cloned();
+        let phrase = LzEndPhrase { src, len, next };
+        i += phrase.len();
+        ends.push(i);
+        res.push(phrase);
+    }
+    res
+}
+
+/// Converts LZ-End phrases into LZ77 factors, which can be decoded by [`super::decode`].
+pub fn lz_end_factors<T>(phrases: &[LzEndPhrase<T>]) -> Vec<Factor<T>>
+where
+    T: Clone,
+{
+    let mut res = vec![];
+    let mut ends = vec![];
+    let mut pos = 0;
+    for phrase in phrases {
+        if phrase.len > 0 {
+            let end: usize = ends[phrase.src];
+            res.push(Factor::Copy {
+                src: end - phrase.len,
+                len: phrase.len,
+            });
+        }
+        if let Some(c) = &phrase.next {
+            res.push(Factor::Literal(c.clone()));
+        }
+        pos += phrase.len();
+        ends.push(pos);
+    }
+    res
+}
+
+#[test]
+fn test_source_policy() {
+    for policy in SourcePolicy::ALL {
+        assert_eq!(policy.to_string().parse(), Ok(policy));
+    }
+    assert!("nearest".parse::<SourcePolicy>().is_err());
+    assert_eq!(SourcePolicy::default(), SourcePolicy::Closest);
+}
+
+#[test]
+fn test_lz77_with() {
+    use super::decode;
+
+    let text = br"abcabcXabcabc";
+    let lit = |c: u8| Factor::Literal(c);
+    let copy = |src, len| Factor::Copy { src, len };
+    let head = [lit(b'a'), lit(b'b'), lit(b'c'), copy(0, 3), lit(b'X')];
+    let factors = lz77_with(text, true, SourcePolicy::Leftmost);
+    assert_eq!(factors, [&head[..], &[copy(0, 6)]].concat());
+    let factors = lz77_with(text, true, SourcePolicy::Rightmost);
+    assert_eq!(factors, [&head[..], &[copy(0, 6)]].concat());
+
+    let text = br"ababXab";
+    let head = [lit(b'a'), lit(b'b'), copy(0, 2), lit(b'X')];
+    let factors = lz77_with(text, true, SourcePolicy::Leftmost);
+    assert_eq!(factors, [&head[..], &[copy(0, 2)]].concat());
+    let factors = lz77_with(text, true, SourcePolicy::Rightmost);
+    assert_eq!(factors, [&head[..], &[copy(2, 2)]].concat());
+
+    // Overlapping copies are forbidden.
+    let factors = lz77_non_overlapping(br"aaaaaaa");
+    assert_eq!(factors, vec![lit(b'a'), copy(0, 1), copy(0, 2), copy(1, 3)]);
+    assert_eq!(decode(&factors).unwrap(), br"aaaaaaa".to_vec());
+}
+
+#[test]
+fn test_lz77_with_naive() {
+    use super::{decode, lz77};
+    use crate::commons::test_texts;
+
+    // Sources of the longest factors at `i` computed naively.
+    fn sources(text: &[u8], i: usize, overlap: bool) -> (usize, Vec<usize>) {
+        let ext = |p: usize| {
+            let bound = if overlap { text.len() } else { i };
+            (0..)
+                .take_while(|&k| p + k < bound && i + k < text.len() && text[p + k] == text[i + k])
+                .count()
+        };
+        let len = (0..i).map(ext).max().unwrap_or(0);
+        let srcs = (0..i).filter(|&p| len > 0 && ext(p) == len).collect();
+        (len, srcs)
+    }
+
+    for text in test_texts() {
+        let closest: Vec<Factor<u8>> = lz77(&text)
+            .into_iter()
+            .map(|(len, occ)| match len {
+                0 => Factor::Literal(text[occ]),
+                len => Factor::Copy { src: occ, len },
+            })
+            .collect();
+        assert_eq!(lz77_with(&text, true, SourcePolicy::Closest), closest);
+        for overlap in [true, false] {
+            for policy in SourcePolicy::ALL {
+                let factors = lz77_with(&text, overlap, policy);
+                assert_eq!(decode(&factors).unwrap(), text);
+                let mut i = 0;
+                for factor in factors {
+                    let (len, srcs) = sources(&text, i, overlap);
+                    match factor {
+                        Factor::Literal(c) => {
+                            assert_eq!((len, c), (0, text[i]));
+                        }
+                        Factor::Copy { src, len: flen } => {
+                            assert_eq!(flen, len);
+                            assert!(srcs.contains(&src));
+                            match policy {
+                                SourcePolicy::Leftmost => assert_eq!(src, srcs[0]),
+                                SourcePolicy::Rightmost => assert_eq!(Some(&src), srcs.last()),
+                                SourcePolicy::Closest => {}
+                            }
+                        }
+                    }
+                    i += max(1, len);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lz_end() {
+    use super::decode;
+    use crate::commons::test_texts;
+
+    let phrase = |src, len, next| LzEndPhrase { src, len, next };
+    // a|b|aa|abaab|a
+    assert_eq!(
+        lz_end(br"abaaabaaba"),
+        vec![
+            phrase(0, 0, Some(b'a')),
+            phrase(0, 0, Some(b'b')),
+            phrase(0, 1, Some(b'a')),
+            phrase(2, 4, Some(b'b')),
+            phrase(2, 1, None),
+        ]
+    );
+
+    for text in test_texts() {
+        let phrases = lz_end(&text);
+        assert_eq!(phrases.iter().map(|p| p.len()).sum::<usize>(), text.len());
+        let factors = lz_end_factors(&phrases);
+        assert_eq!(decode(&factors).unwrap(), text);
+
+        // Every copy is the longest one ending at a phrase end.
+        let mut ends = vec![];
+        let mut i = 0;
+        for phrase in phrases {
+            let len = ends
+                .iter()
+                .flat_map(|&e: &usize| {
+                    let text = &text;
+                    (1..=e).filter(move |&l| text[i..].starts_with(&text[e - l..e]))
+                })
+                .max()
+                .unwrap_or(0);
+            assert_eq!(phrase.len, len);
+            i += phrase.len();
+            ends.push(i);
+        }
+    }
+}
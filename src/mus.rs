@@ -9,7 +9,7 @@ pub fn minimum_unique_substrs<T>(text: &[T]) -> Vec<Vec<T>>
 where
     T: Ord + Clone,
 {
-    let sa = make_sa(text);
+    let sa: Vec<usize> = make_sa(text);
     let lcpa = make_lcpa(text, &sa);
     minimum_unique_substrs_aux(text, &sa, &lcpa)
 }
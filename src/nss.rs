@@ -12,10 +12,7 @@ pub fn nss_naive<T>(text: &[T]) -> Vec<usize>
 where
     T: Ord,
 {
-    (0..text.len())
-        .into_iter()
-        .map(|i| nssi_naive(text, i))
-        .collect()
+    (0..text.len()).map(|i| nssi_naive(text, i)).collect()
 }
 
 pub fn nssi_naive<T>(text: &[T], i: usize) -> usize
@@ -0,0 +1,277 @@
+//! Run-Length encoded Burrows-Wheeler Transform (RLBWT)
+//!
+//! Let $r$ be the number of maximal runs of equal characters in the BWT of $T\\$$.
+//! The RLBWT stores the BWT in $O(r)$ words, and supports LF-mapping and backward search.
+//! It also supports locating occurrences of patterns in $O(r)$ words by the toehold lemma and
+//! the $\phi$ function as in the r-index (Gagie, Navarro and Prezza, 2018).
+
+use std::ops::Range;
+
+use crate::bwt::bwt_from_sa;
+use crate::suffix_array::sais::make_sa_induce;
+
+/// Code of the sentinel in the BWT.
+const SENTINEL: u32 = 0;
+
+/// Run-length encoded BWT supporting r-index style locate queries.
+pub struct RLBWT<T> {
+    /// Distinct characters of the text in ascending order.
+    /// A character `alphabet[c]` is encoded as `c+1` in the BWT.
+    alphabet: Vec<T>,
+    /// Length of the BWT, i.e., the length of the text with the sentinel.
+    len: usize,
+    /// `heads[k]` is the character of the `k`-th run.
+    heads: Vec<u32>,
+    /// `starts[k]` is the beginning row of the `k`-th run.
+    starts: Vec<usize>,
+    /// `c_arr[c]` is the number of characters less than `c` in the text with the sentinel.
+    c_arr: Vec<usize>,
+    /// `char_runs[c]` stores the indexes of runs of character `c` in ascending order.
+    char_runs: Vec<Vec<usize>>,
+    /// `char_lens[c][m]` is the total length of the first `m` runs of character `c`.
+    char_lens: Vec<Vec<usize>>,
+    /// `end_samples[k]` is the text position of the suffix at the last row of the `k`-th run.
+    end_samples: Vec<usize>,
+    /// Text positions of the suffixes at the first rows of runs, except for the row `0`, in ascending order.
+    phi_keys: Vec<usize>,
+    /// `phi_vals[m]` is the text position of the suffix at the row preceding the row of `phi_keys[m]`.
+    phi_vals: Vec<usize>,
+}
+
+impl<T> RLBWT<T>
+where
+    T: Ord + Clone,
+{
+    /// Builds the RLBWT of a given text.
+    pub fn new(text: &[T]) -> Self {
+        let n = text.len();
+        let mut alphabet = text.to_vec();
+        alphabet.sort();
+        alphabet.dedup();
+        let codes: Vec<u32> = text
+            .iter()
+            .map(|c| alphabet.binary_search(c).unwrap() as u32)
+            .collect();
+        let sa: Vec<usize> = make_sa_induce(&codes);
+        let bwt: Vec<u32> = bwt_from_sa(&codes, &sa)
+            .into_iter()
+            .map(|c| c.map_or(SENTINEL, |c| c + 1))
+            .collect();
+        // The suffix array of the text with the sentinel is `[n] + sa`.
+        let suf = |i: usize| if i == 0 { n } else { sa[i - 1] };
+
+        let sigma = alphabet.len() + 1;
+        let mut c_arr = vec![0; sigma + 1];
+        bwt.iter().for_each(|&c| c_arr[c as usize + 1] += 1);
+        for c in 1..c_arr.len() {
+            c_arr[c] += c_arr[c - 1];
+        }
+
+        let mut heads = vec![];
+        let mut starts = vec![];
+        let mut end_samples = vec![];
+        let mut char_runs = vec![vec![]; sigma];
+        let mut char_lens = vec![vec![0]; sigma];
+        let mut phi = vec![];
+        for i in 0..bwt.len() {
+            if i == 0 || bwt[i] != bwt[i - 1] {
+                let c = bwt[i] as usize;
+                char_runs[c].push(heads.len());
+                let total = *char_lens[c].last().unwrap();
+                char_lens[c].push(total);
+                heads.push(bwt[i]);
+                starts.push(i);
+                if i > 0 {
+                    phi.push((suf(i), suf(i - 1)));
+                }
+            }
+            *char_lens[bwt[i] as usize].last_mut().unwrap() += 1;
+            if i + 1 == bwt.len() || bwt[i] != bwt[i + 1] {
+                end_samples.push(suf(i));
+            }
+        }
+        phi.sort_unstable();
+        let (phi_keys, phi_vals) = phi.into_iter().unzip();
+
+        Self {
+            alphabet,
+            len: bwt.len(),
+            heads,
+            starts,
+            c_arr,
+            char_runs,
+            char_lens,
+            end_samples,
+            phi_keys,
+            phi_vals,
+        }
+    }
+
+    /// Returns the length of the text.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len - 1
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of runs in the BWT.
+    pub fn num_runs(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Returns the code of a character, or `None` if it does not appear in the text.
+    fn code(&self, c: &T) -> Option<usize> {
+        self.alphabet.binary_search(c).ok().map(|c| c + 1)
+    }
+
+    /// Returns the index of the run containing row `i`.
+    #[inline(always)]
+    fn run(&self, i: usize) -> usize {
+        self.starts.partition_point(|&s| s <= i) - 1
+    }
+
+    /// Returns the last row of the `k`-th run.
+    #[inline(always)]
+    fn run_end(&self, k: usize) -> usize {
+        match self.starts.get(k + 1) {
+            Some(&s) => s - 1,
+            None => self.len - 1,
+        }
+    }
+
+    /// Returns the number of occurrences of code `c` in `bwt[..i]`.
+    fn rank(&self, c: usize, i: usize) -> usize {
+        if i == self.len {
+            return *self.char_lens[c].last().unwrap();
+        }
+        let k = self.run(i);
+        let m = self.char_runs[c].partition_point(|&x| x < k);
+        let mut res = self.char_lens[c][m];
+        if self.heads[k] as usize == c {
+            res += i - self.starts[k];
+        }
+        res
+    }
+
+    /// Returns the character at row `i` of the BWT, or `None` for the sentinel.
+    pub fn access(&self, i: usize) -> Option<T> {
+        match self.heads[self.run(i)] {
+            SENTINEL => None,
+            c => Some(self.alphabet[c as usize - 1].clone()),
+        }
+    }
+
+    /// LF-mapping, which maps the row of a suffix beginning at `j` to the row of the suffix beginning at `j-1`.
+    pub fn lf(&self, i: usize) -> usize {
+        let c = self.heads[self.run(i)] as usize;
+        self.c_arr[c] + self.rank(c, i)
+    }
+
+    /// Returns the interval of rows of the BWT whose suffixes begin with a given pattern,
+    /// and the text position of the suffix at the last row of the interval,
+    /// or `None` if the pattern does not occur.
+    fn range_toehold(&self, pattern: &[T]) -> Option<(Range<usize>, usize)> {
+        let (mut beg, mut end) = (if pattern.is_empty() { 1 } else { 0 }, self.len);
+        if beg == end {
+            // The empty pattern in the empty text.
+            return None;
+        }
+        let mut toehold = *self.end_samples.last().unwrap();
+        for x in pattern.iter().rev() {
+            let c = self.code(x)?;
+            // Finds the last row `j` in `[beg, end)` such that `bwt[j]=c`, and its text position.
+            let k = self.run(end - 1);
+            let pos = if self.heads[k] as usize == c {
+                toehold
+            } else {
+                let m = self.char_runs[c].partition_point(|&x| x < k);
+                if m == 0 || self.run_end(self.char_runs[c][m - 1]) < beg {
+                    return None;
+                }
+                self.end_samples[self.char_runs[c][m - 1]]
+            };
+            beg = self.c_arr[c] + self.rank(c, beg);
+            end = self.c_arr[c] + self.rank(c, end);
+            debug_assert!(beg < end);
+            toehold = pos - 1;
+        }
+        Some((beg..end, toehold))
+    }
+
+    /// Returns the interval of rows of the BWT whose suffixes begin with a given pattern.
+    pub fn range(&self, pattern: &[T]) -> Range<usize> {
+        self.range_toehold(pattern).map_or(0..0, |x| x.0)
+    }
+
+    /// Returns the number of occurrences of a given pattern.
+    pub fn count(&self, pattern: &[T]) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Returns the text position of the suffix at the row preceding the row of the suffix beginning at `p`.
+    fn phi(&self, p: usize) -> usize {
+        let m = self.phi_keys.partition_point(|&x| x <= p) - 1;
+        self.phi_vals[m] + (p - self.phi_keys[m])
+    }
+
+    /// Returns the beginning positions of occurrences of a given pattern in ascending order.
+    pub fn locate(&self, pattern: &[T]) -> Vec<usize> {
+        let (range, mut p) = match self.range_toehold(pattern) {
+            Some(x) => x,
+            None => return vec![],
+        };
+        if range.is_empty() {
+            return vec![];
+        }
+        let mut res = Vec::with_capacity(range.len());
+        res.push(p);
+        for _ in 1..range.len() {
+            p = self.phi(p);
+            res.push(p);
+        }
+        res.sort_unstable();
+        res
+    }
+}
+
+#[test]
+fn test_rlbwt() {
+    let text = br"banana";
+    let rlbwt = RLBWT::new(text);
+    // The BWT is `annb$aa`.
+    assert_eq!(rlbwt.num_runs(), 5);
+    let bwt: Vec<Option<u8>> = (0..=text.len()).map(|i| rlbwt.access(i)).collect();
+    assert_eq!(bwt, crate::bwt::bwt(text));
+    assert_eq!(rlbwt.count(br"ana"), 2);
+    assert_eq!(rlbwt.locate(br"ana"), vec![1, 3]);
+    assert_eq!(rlbwt.locate(br"a"), vec![1, 3, 5]);
+    assert!(rlbwt.locate(br"nab").is_empty());
+    assert_eq!(rlbwt.locate(br""), (0..text.len()).collect::<Vec<_>>());
+
+    let rlbwt = RLBWT::<u8>::new(br"");
+    assert_eq!(rlbwt.count(br""), 0);
+    assert!(rlbwt.locate(br"").is_empty());
+    assert!(rlbwt.locate(br"a").is_empty());
+}
+
+#[test]
+fn test_rlbwt_naive() {
+    use crate::commons::{locate_naive, test_patterns, test_texts};
+
+    for text in test_texts() {
+        let rlbwt = RLBWT::new(&text);
+        let lf = crate::bwt::lf_array(&crate::bwt::bwt(&text));
+        for (i, &x) in lf.iter().enumerate() {
+            assert_eq!(rlbwt.lf(i), x);
+        }
+        for pat in test_patterns(&text) {
+            let ans = locate_naive(&text, &pat);
+            assert_eq!(rlbwt.count(&pat), ans.len());
+            assert_eq!(rlbwt.locate(&pat), ans);
+        }
+    }
+}
@@ -1,15 +1,22 @@
 use std::cmp::max;
 use std::convert::TryFrom;
 
+use crate::suffix_array::misc::SaIndex;
 use crate::suffix_array::sais::make_sa_induce;
+use crate::suffix_array::search::SuffixArraySearch;
 
 /// Relative LZ
-pub struct RLZ {
+///
+/// `I` is the index type of the suffix array of the reference text.
+pub struct RLZ<I = u32> {
     pub text: Vec<u8>,
-    sa: Vec<u32>,
+    sa: Vec<I>,
 }
 
-impl RLZ {
+impl<I> RLZ<I>
+where
+    I: SaIndex,
+{
     pub fn new(text: Vec<u8>) -> Self {
         let sa = make_sa_induce(&text);
         Self { text, sa }
@@ -25,88 +32,24 @@ impl RLZ {
         self.len() == 0
     }
 
-    /// Returns the lowest position in the range [beg, end] that a suffix have
-    /// a character greater than or equal to `c` at position `len`.
-    /// Note that `end` may be `self.len()` which is the out of the suffix array.
-    fn lower_bound(&self, mut beg: usize, mut end: usize, len: usize, c: u8) -> usize {
-        let (beg_orig, end_orig) = (beg, end);
-        // println!("lower_bound({}, {}, {}, {})", beg, end, len, c);
-        while beg < end {
-            let mid = (beg + end) / 2;
-            let i = self.sa[mid] as usize + len;
-            if i >= self.len() || self.text[i] < c {
-                beg = mid + 1;
-            } else {
-                end = mid;
-            }
-        }
-
-        if 0 < beg && beg < end_orig && beg_orig < beg {
-            let b0 = self.sa[beg - 1] as usize + len;
-            let b1 = self.sa[beg] as usize + len;
-            if b0 < self.len() && b1 < self.len() {
-                // println!("c={}, (b0, b1)=({}, {})", c, self.text[b0], self.text[b1]);
-                debug_assert!((self.text[b0] < c && c <= self.text[b1]));
-            }
-        }
-        beg
-    }
-
-    /// Returns the lowest position in the range [beg, end] that a suffix have
-    /// a character greater than `c` at position `len`.
-    /// Note that `end` may be `self.len()` which is the out of the suffix array.
-    fn upper_bound(&self, mut beg: usize, mut end: usize, len: usize, c: u8) -> usize {
-        while beg < end {
-            // while beg + 1 < end {
-            let mid = (beg + end) / 2;
-            let i = self.sa[mid] as usize + len;
-            if i >= self.len() || self.text[i] <= c {
-                beg = mid + 1;
-            } else {
-                end = mid;
-            }
-        }
-        beg
-    }
-
     /// Prints the suffixes in the range `[beg, end)`.
     pub fn print_suffix_range(&self, beg: usize, end: usize, len: usize) {
         for i in beg..end {
-            let j = self.sa[i] as usize;
+            let j = self.sa[i].into_usize();
             println!("[{}]: {:?}", i, &self.text[j..(j + len)]);
         }
     }
 
-    /// Finds the next suffix interval that the given suffix interval can be extended with a given character.
-    fn extend_range(&self, beg: usize, end: usize, len: usize, c: u8) -> (usize, usize) {
-        (
-            self.lower_bound(beg, end, len, c),
-            self.upper_bound(beg, end, len, c),
-        )
+    /// Returns a searcher over the suffix array of the reference text.
+    pub fn searcher(&self) -> SuffixArraySearch<'_, u8, I> {
+        SuffixArraySearch::new(&self.text, &self.sa)
     }
 
     /// Finds suffix interval on the suffix array such that the suffixes on the interval have common prefix and they are longest.
     /// Returns a tuple beginning and ending of the interval, and its length.
     pub fn lcp_range(&self, pat: &[u8]) -> (usize, usize, usize) {
-        let mut res = 0;
-        let mut beg = 0;
-        let mut end = self.len();
-        for (i, &c) in pat.iter().enumerate() {
-            let (beg2, end2) = self.extend_range(beg, end, i, c);
-            if beg2 == end2 {
-                res = i;
-                break;
-            }
-            debug_assert_eq!(self.text[self.sa[beg2] as usize + i], c);
-            debug_assert_eq!(self.text[self.sa[end2 - 1] as usize + i], c);
-            (beg, end) = (beg2, end2);
-            res += 1;
-        }
-        if res > 0 {
-            let b = self.sa[beg] as usize;
-            debug_assert_eq!(pat[..res], self.text[b..b + res]);
-        }
-        (beg, end, res)
+        let (range, len) = self.searcher().longest_prefix_match(pat);
+        (range.start, range.end, len)
     }
 
     /// Returns a factor that equals a prefix of a given pattern.
@@ -117,7 +60,7 @@ impl RLZ {
         let (beg, _, len) = self.lcp_range(pat);
         match len {
             0 => (0, pat[0] as usize),
-            _ => (len, self.sa[beg] as usize),
+            _ => (len, self.sa[beg].into_usize()),
         }
     }
 
@@ -162,7 +105,7 @@ impl RLZ {
 fn test_rlz() {
     let text1 = br"abaaab";
     let text2 = br"aaaaab";
-    let rlz = RLZ::new(text1.to_vec());
+    let rlz: RLZ = RLZ::new(text1.to_vec());
     let factors = rlz.encode_factors(text2);
     assert_eq!(factors, vec![(3, 2), (3, 3)]);
 }
@@ -18,3 +18,43 @@ impl StreeSerde {
         file.write(self.serialize().as_bytes())
     }
 }
+
+/// Strings of characters that can be shown as labels of nodes and edges.
+pub trait Label: Sized {
+    /// Returns the label of a string.
+    fn label(s: &[Self]) -> String;
+}
+
+impl Label for u8 {
+    /// Bytes out of printable ASCII are escaped, e.g., `\xff`.
+    fn label(s: &[Self]) -> String {
+        s.escape_ascii().to_string()
+    }
+}
+
+impl Label for char {
+    fn label(s: &[Self]) -> String {
+        s.iter().collect()
+    }
+}
+
+macro_rules! int_label {
+    ($($type:ident),*) => {
+        $(impl Label for $type {
+            /// Integers are separated by commas.
+            fn label(s: &[Self]) -> String {
+                s.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")
+            }
+        })*
+    };
+}
+
+int_label!(u16, u32, u64, usize);
+
+#[test]
+fn test_label() {
+    assert_eq!(Label::label(br"abc"), "abc");
+    assert_eq!(Label::label(&[b'a', 0xff]), "a\\xff");
+    assert_eq!(Label::label(&['a', 'あ']), "aあ");
+    assert_eq!(Label::label(&[1u32, 20, 300]), "1,20,300");
+}
@@ -1,92 +1,323 @@
-use std::collections::{HashMap, HashSet};
+//! Statistics of substrings of a text
+//!
+//! [`StrStat`] answers queries on substrings with the suffix trees of the text and its reverse in $O(n)$ space,
+//! instead of enumerating all the $\Theta(n^2)$ substrings as [`crate::commons`] does.
 
-use crate::tools::*;
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    ops::{Range, RangeInclusive},
+};
 
-use crate::bstr;
+use crate::suffix_tree::ukkonnen_vec_lib::Tree;
 
-pub struct StrStat<'text> {
-    pub text: &'text bstr,
+/// Group of non-empty substrings that have the same beginning positions, or the same ending positions.
+///
+/// The members of a group of the same beginning positions are the prefixes of the longest member
+/// whose lengths are in `lens`, and the ones of a group of the same ending positions are the suffixes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Group<'text, T> {
+    /// Node of the suffix tree of the text, or the one of the reversed text, corresponding to the group.
+    pub nid: usize,
+    pub longest: &'text [T],
+    pub lens: RangeInclusive<usize>,
+    /// Number of occurrences of each member.
+    pub occ: usize,
+}
+
+pub struct StrStat<'text, T> {
+    pub text: &'text [T],
     pub n: usize,
-    // pub epsilon: &'text bstr,
-    sigma: HashSet<&'text bstr>,
-    pres: HashSet<&'text bstr>,
-    ppres: HashSet<&'text bstr>,
-    sufs: HashSet<&'text bstr>,
-    psufs: HashSet<&'text bstr>,
-    substrs: HashSet<&'text bstr>,
-    occ_map: HashMap<&'text bstr, usize>,
-    lgmap: HashMap<&'text bstr, HashSet<&'text bstr>>,
-    rgmap: HashMap<&'text bstr, HashSet<&'text bstr>>,
+    /// Suffix tree of the text.
+    tree: Tree<T>,
+    /// Suffix tree of the reversed text.
+    rev_tree: Tree<T>,
 }
 
-impl<'text> StrStat<'text> {
-    pub fn new(w: &'text bstr) -> Self {
-        let epsilon: &bstr = &[];
-        let sigma = sigma(w);
-        let pres = pref_set(w);
-        let mut ppres = pres.clone();
-        ppres.remove(epsilon);
-        let sufs = suf_set(w);
-        let mut psufs = sufs.clone();
-        psufs.remove(epsilon);
-        let subs = substrs(w);
-        let lgmap = leq_groups(&subs, &pres);
-        let rgmap = req_groups(&subs, &sufs);
-        let suf_trie = suffix_trie(&sigma, &subs);
+impl<'text, T> StrStat<'text, T>
+where
+    T: Ord + Hash + Clone,
+{
+    pub fn new(w: &'text [T]) -> Self {
+        let tree = Tree::from_text_sa(w);
+        let rev: Vec<T> = w.iter().rev().cloned().collect();
+        let rev_tree = Tree::from_text_sa(&rev);
         Self {
             text: w,
             n: w.len(),
-            // epsilon: &[],
-            sigma,
-            pres: pres,
-            ppres: ppres,
-            sufs: sufs,
-            psufs: psufs,
-            substrs: subs,
-            occ_map: nocc(w),
-            lgmap,
-            rgmap,
-            // suf_trie: suf_trie,
+            tree,
+            rev_tree,
         }
     }
 
-    pub fn nocc(&self, w: &bstr) -> usize {
-        *self.occ_map.get(w).unwrap_or(&0)
+    /// Returns the number of occurrences of a given non-empty string, or `0` for the empty string.
+    pub fn nocc(&self, w: &[T]) -> usize {
+        if w.is_empty() {
+            return 0;
+        }
+        self.tree.count_occurrences(w)
     }
 
-    pub fn left_maximal(&self, sub: &bstr) -> &bstr {
-        self.rgmap
-            .get(sub)
-            .expect("has substring.")
-            .iter()
-            .max_by_key(|k| k.len())
-            .expect("has at least one element.")
+    /// Returns the longest substring that has the same ending positions as a given substring `sub`.
+    pub fn left_maximal(&self, sub: &[T]) -> Option<&'text [T]> {
+        let rev: Vec<T> = sub.iter().rev().cloned().collect();
+        let nid = self.rev_tree.find(&rev)?;
+        Some(&self.text[self.unreverse(self.rev_tree.label_range(nid))])
     }
 
-    pub fn right_maximal(&self, sub: &bstr) -> &bstr {
-        self.lgmap
-            .get(sub)
-            .expect("has substring.")
-            .iter()
-            .max_by_key(|k| k.len())
-            .expect("has at least one element.")
+    /// Returns the longest substring that has the same beginning positions as a given substring `sub`.
+    pub fn right_maximal(&self, sub: &[T]) -> Option<&'text [T]> {
+        let nid = self.tree.find(sub)?;
+        Some(&self.text[self.tree.label_range(nid)])
     }
 
     /// set of characters that appear in the left on the given substring `sub`.
-    pub fn left_extention(&self, sub: &bstr) -> HashSet<&bstr> {
-        self.sigma
-            .iter()
-            .filter(|&c| self.substrs.contains([c, sub].concat().as_slice()))
-            .map(|k| *k)
-            .collect()
+    pub fn left_extention(&self, sub: &[T]) -> HashSet<T> {
+        let rev: Vec<T> = sub.iter().rev().cloned().collect();
+        Self::extention(&self.rev_tree, &rev)
     }
 
     /// set of characters that appear in the right on the given substring `sub`.
-    pub fn right_extention(&self, sub: &bstr) -> HashSet<&bstr> {
-        self.sigma
-            .iter()
-            .filter(|&c| self.substrs.contains([sub, c].concat().as_slice()))
-            .map(|k| *k)
+    pub fn right_extention(&self, sub: &[T]) -> HashSet<T> {
+        Self::extention(&self.tree, sub)
+    }
+
+    /// Returns the beginning positions of a given string in ascending order.
+    pub fn beg_pos(&self, x: &[T]) -> Vec<usize> {
+        self.tree.locate(x)
+    }
+
+    /// Returns the ending positions of a given string in ascending order.
+    pub fn end_pos(&self, x: &[T]) -> Vec<usize> {
+        let rev: Vec<T> = x.iter().rev().cloned().collect();
+        let mut res: Vec<usize> = self
+            .rev_tree
+            .locate(&rev)
+            .into_iter()
+            .map(|pos| self.n - 1 - pos)
+            .collect();
+        res.reverse();
+        res
+    }
+
+    /// Returns the group of substrings that have the same beginning positions as a given non-empty substring.
+    pub fn bpos_group(&self, sub: &[T]) -> Option<Group<'text, T>> {
+        if sub.is_empty() {
+            return None;
+        }
+        self.group(false, self.tree.find(sub)?)
+    }
+
+    /// Returns the group of substrings that have the same ending positions as a given non-empty substring.
+    pub fn epos_group(&self, sub: &[T]) -> Option<Group<'text, T>> {
+        if sub.is_empty() {
+            return None;
+        }
+        let rev: Vec<T> = sub.iter().rev().cloned().collect();
+        self.group(true, self.rev_tree.find(&rev)?)
+    }
+
+    /// Returns the partition of the non-empty substrings by their beginning positions,
+    /// which corresponds to the edges of the suffix tree.
+    pub fn bpos_groups(&self) -> Vec<Group<'text, T>> {
+        (0..self.tree.num_nodes())
+            .filter_map(|nid| self.group(false, nid))
             .collect()
     }
+
+    /// Returns the partition of the non-empty substrings by their ending positions,
+    /// which corresponds to the edges of the suffix tree of the reversed text.
+    pub fn epos_groups(&self) -> Vec<Group<'text, T>> {
+        (0..self.rev_tree.num_nodes())
+            .filter_map(|nid| self.group(true, nid))
+            .collect()
+    }
+
+    /// Returns the number of distinct non-empty substrings.
+    pub fn num_substrs(&self) -> usize {
+        self.bpos_groups()
+            .iter()
+            .map(|g| g.lens.clone().count())
+            .sum()
+    }
+
+    /// Returns the distinct non-empty substrings without copying them.
+    pub fn substrs(&self) -> impl Iterator<Item = &'text [T]> {
+        self.bpos_groups()
+            .into_iter()
+            .flat_map(|Group { longest, lens, .. }| lens.map(move |len| &longest[..len]))
+    }
+
+    /// Returns the group corresponding to node `nid` of the suffix tree of the text, or of the reversed text if `rev`,
+    /// unless it is the root or a leaf whose edge consists of the terminator only.
+    fn group(&self, rev: bool, nid: usize) -> Option<Group<'text, T>> {
+        let tree = if rev { &self.rev_tree } else { &self.tree };
+        let pid = tree.parent(nid)?;
+        let range = tree.label_range(nid);
+        let lens = tree.depth(pid) + 1..=range.len();
+        if lens.is_empty() {
+            return None;
+        }
+        let range = if rev { self.unreverse(range) } else { range };
+        Some(Group {
+            nid,
+            longest: &self.text[range],
+            lens,
+            occ: tree.leaf_counts()[nid],
+        })
+    }
+
+    /// Maps a range of the reversed text to the range of the text.
+    fn unreverse(&self, range: Range<usize>) -> Range<usize> {
+        self.n - range.end..self.n - range.start
+    }
+
+    /// Returns the characters `c` such that `sub` followed by `c` occurs in the text of `tree`.
+    fn extention(tree: &Tree<T>, sub: &[T]) -> HashSet<T> {
+        let text = tree.text();
+        match tree.find(sub) {
+            None => HashSet::new(),
+            // `sub` ends in the middle of the edge to the node.
+            Some(nid) if sub.len() < tree.depth(nid) => {
+                HashSet::from([text[tree.label_range(nid).start + sub.len()].clone()])
+            }
+            Some(nid) => tree
+                .children(nid)
+                .map(|cid| tree.edge_range(cid))
+                .filter(|range| !range.is_empty())
+                .map(|range| text[range.start].clone())
+                .collect(),
+        }
+    }
+}
+
+#[test]
+fn test_strstat() {
+    let stat = StrStat::new(br"banana");
+    assert_eq!(stat.nocc(br"ana"), 2);
+    assert_eq!(stat.nocc(br"nab"), 0);
+    assert_eq!(stat.left_maximal(br"na"), Some(&br"ana"[..]));
+    assert_eq!(stat.right_maximal(br"an"), Some(&br"ana"[..]));
+    assert_eq!(stat.right_maximal(br"b"), Some(&br"banana"[..]));
+    assert_eq!(stat.right_maximal(br"nab"), None);
+    assert_eq!(stat.left_extention(br"a"), HashSet::from([b'b', b'n']));
+    assert_eq!(stat.right_extention(br"a"), HashSet::from([b'n']));
+    assert!(stat.right_extention(br"nana").is_empty());
+}
+
+#[test]
+fn test_strstat_naive() {
+    use crate::commons::{
+        alphabet_set, bpos_groups, epos_groups, left_maximal, locate_naive, right_maximal, substrs,
+        test_texts,
+    };
+
+    // The naive groups are computed only for short texts.
+    for text in test_texts()
+        .into_iter()
+        .filter(|x| !x.is_empty() && x.len() <= 12)
+    {
+        let stat = StrStat::new(&text);
+        let subs = substrs(&text);
+        let bgmap = bpos_groups(&text);
+        let egmap = epos_groups(&text);
+        let sigma = alphabet_set(&text);
+        for sub in subs.iter() {
+            let occ = if sub.is_empty() {
+                0
+            } else {
+                locate_naive(&text, sub).len()
+            };
+            assert_eq!(stat.nocc(sub), occ);
+            assert_eq!(stat.left_maximal(sub), Some(&left_maximal(sub, &egmap)[..]));
+            assert_eq!(
+                stat.right_maximal(sub),
+                Some(&right_maximal(sub, &bgmap)[..])
+            );
+            let left: HashSet<u8> = sigma
+                .iter()
+                .copied()
+                .filter(|&c| subs.contains(&[&[c], &sub[..]].concat()))
+                .collect();
+            assert_eq!(stat.left_extention(sub), left);
+            let right: HashSet<u8> = sigma
+                .iter()
+                .copied()
+                .filter(|&c| subs.contains(&[&sub[..], &[c]].concat()))
+                .collect();
+            assert_eq!(stat.right_extention(sub), right);
+        }
+        assert_eq!(stat.left_maximal(br"xxx"), None);
+        assert!(stat.left_extention(br"xxx").is_empty());
+    }
+}
+
+#[test]
+fn test_strstat_groups() {
+    use crate::commons::{
+        bpos_groups, end_pos, enum_strs_len_leq, epos_groups, locate_naive, substrs, test_texts,
+    };
+
+    let stat = StrStat::new(br"cocoa");
+    let group = stat.bpos_group(br"oc").unwrap();
+    assert_eq!(group.longest, br"ocoa");
+    assert_eq!(group.lens, 2..=4);
+    assert_eq!(group.occ, 1);
+    let group = stat.epos_group(br"o").unwrap();
+    assert_eq!(group.longest, br"co");
+    assert_eq!(group.lens, 1..=2);
+    assert_eq!(group.occ, 2);
+    assert_eq!(stat.beg_pos(br"co"), vec![0, 2]);
+    assert_eq!(stat.end_pos(br"co"), vec![1, 3]);
+
+    for text in test_texts()
+        .into_iter()
+        .filter(|x| !x.is_empty() && x.len() <= 12)
+    {
+        let stat = StrStat::new(&text);
+        let mut subs = substrs(&text);
+        subs.remove(&vec![]);
+        assert_eq!(stat.num_substrs(), subs.len());
+        assert_eq!(
+            stat.substrs().map(|x| x.to_vec()).collect::<HashSet<_>>(),
+            subs
+        );
+        for (groups, naive, rev) in [
+            (stat.bpos_groups(), bpos_groups(&text), false),
+            (stat.epos_groups(), epos_groups(&text), true),
+        ] {
+            assert_eq!(
+                groups.iter().map(|g| g.lens.clone().count()).sum::<usize>(),
+                subs.len()
+            );
+            for group in groups {
+                let members: HashSet<Vec<u8>> = group
+                    .lens
+                    .clone()
+                    .map(|len| {
+                        let k = if rev { group.longest.len() - len } else { 0 };
+                        group.longest[k..k + len].to_vec()
+                    })
+                    .collect();
+                assert_eq!(members, naive[group.longest]);
+                assert_eq!(group.occ, locate_naive(&text, group.longest).len());
+            }
+        }
+        for sub in subs.iter() {
+            assert_eq!(
+                stat.bpos_group(sub).unwrap().longest,
+                stat.right_maximal(sub).unwrap()
+            );
+            assert_eq!(
+                stat.epos_group(sub).unwrap().longest,
+                stat.left_maximal(sub).unwrap()
+            );
+        }
+        for pat in enum_strs_len_leq(br"ab", 3) {
+            assert_eq!(stat.beg_pos(&pat), locate_naive(&text, &pat));
+            let mut epos: Vec<usize> = end_pos(&text, &pat).into_iter().collect();
+            epos.sort_unstable();
+            assert_eq!(stat.end_pos(&pat), epos);
+        }
+    }
 }
@@ -1,3 +1,7 @@
+pub mod builder;
+pub mod dc3;
 pub mod misc;
 pub mod sais;
+pub mod samm;
 pub mod sanaive;
+pub mod search;
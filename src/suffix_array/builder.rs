@@ -0,0 +1,295 @@
+//! Common interface of suffix array construction algorithms.
+//!
+//! Every algorithm accepts a text over an arbitrary ordered alphabet and returns `Vec<I>`,
+//! and fails with [`SaError`] instead of panicking if the text is too long for the index type `I`.
+//! [`SaAlgorithm`] chooses an algorithm at runtime, e.g., from a command line option.
+
+use std::{fmt, str::FromStr};
+
+use super::dc3::make_sa_dc3;
+use super::misc::SaIndex;
+use super::sais::make_sa_induce_ord;
+use super::samm::make_sa_mm;
+use super::sanaive::make_sa_naive;
+
+/// Errors of suffix array construction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SaError {
+    /// The text of length `len` is too long for the index type, which allows texts of length at most `max`.
+    TooLong { len: usize, max: usize },
+}
+
+impl fmt::Display for SaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaError::TooLong { len, max } => write!(
+                f,
+                "the text of length {} is too long for the index type (at most {})",
+                len, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaError {}
+
+/// Suffix array construction algorithm.
+pub trait SuffixArrayBuilder {
+    /// Returns the name of the algorithm.
+    fn name(&self) -> &'static str;
+
+    /// Make a suffix array assuming that the text is short enough for the index type.
+    fn build_unchecked<T, I>(&self, text: &[T]) -> Vec<I>
+    where
+        T: Ord,
+        I: SaIndex;
+
+    /// Make a suffix array of a given text.
+    fn build<T, I>(&self, text: &[T]) -> Result<Vec<I>, SaError>
+    where
+        T: Ord,
+        I: SaIndex,
+    {
+        // `I::MAX` is reserved as an empty slot of suffix arrays.
+        let max = I::MAX.into_usize() - 1;
+        if text.len() > max {
+            return Err(SaError::TooLong {
+                len: text.len(),
+                max,
+            });
+        }
+        Ok(self.build_unchecked(text))
+    }
+}
+
+/// Sorting suffixes by comparisons in $O(n^2 \log n)$ time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Naive;
+
+/// Prefix doubling of Manber and Myers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PrefixDoubling;
+
+/// Induced sorting (SA-IS) of Nong, Zhang and Chan in $O(n)$ time after reducing the alphabet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InducedSorting;
+
+/// DC3 (skew) algorithm of Kärkkäinen and Sanders in $O(n)$ time after reducing the alphabet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DifferenceCover;
+
+impl SuffixArrayBuilder for Naive {
+    fn name(&self) -> &'static str {
+        "naive"
+    }
+
+    fn build_unchecked<T, I>(&self, text: &[T]) -> Vec<I>
+    where
+        T: Ord,
+        I: SaIndex,
+    {
+        make_sa_naive(text)
+    }
+}
+
+impl SuffixArrayBuilder for PrefixDoubling {
+    fn name(&self) -> &'static str {
+        "doubling"
+    }
+
+    fn build_unchecked<T, I>(&self, text: &[T]) -> Vec<I>
+    where
+        T: Ord,
+        I: SaIndex,
+    {
+        make_sa_mm(text)
+    }
+}
+
+impl SuffixArrayBuilder for InducedSorting {
+    fn name(&self) -> &'static str {
+        "sais"
+    }
+
+    fn build_unchecked<T, I>(&self, text: &[T]) -> Vec<I>
+    where
+        T: Ord,
+        I: SaIndex,
+    {
+        make_sa_induce_ord(text)
+    }
+}
+
+impl SuffixArrayBuilder for DifferenceCover {
+    fn name(&self) -> &'static str {
+        "dc3"
+    }
+
+    fn build_unchecked<T, I>(&self, text: &[T]) -> Vec<I>
+    where
+        T: Ord,
+        I: SaIndex,
+    {
+        make_sa_dc3(text)
+    }
+}
+
+/// Suffix array construction algorithm chosen at runtime.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SaAlgorithm {
+    Naive,
+    PrefixDoubling,
+    #[default]
+    InducedSorting,
+    DifferenceCover,
+}
+
+impl SaAlgorithm {
+    /// All the algorithms.
+    pub const ALL: [SaAlgorithm; 4] = [
+        SaAlgorithm::Naive,
+        SaAlgorithm::PrefixDoubling,
+        SaAlgorithm::InducedSorting,
+        SaAlgorithm::DifferenceCover,
+    ];
+}
+
+impl SuffixArrayBuilder for SaAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            SaAlgorithm::Naive => Naive.name(),
+            SaAlgorithm::PrefixDoubling => PrefixDoubling.name(),
+            SaAlgorithm::InducedSorting => InducedSorting.name(),
+            SaAlgorithm::DifferenceCover => DifferenceCover.name(),
+        }
+    }
+
+    fn build_unchecked<T, I>(&self, text: &[T]) -> Vec<I>
+    where
+        T: Ord,
+        I: SaIndex,
+    {
+        match self {
+            SaAlgorithm::Naive => Naive.build_unchecked(text),
+            SaAlgorithm::PrefixDoubling => PrefixDoubling.build_unchecked(text),
+            SaAlgorithm::InducedSorting => InducedSorting.build_unchecked(text),
+            SaAlgorithm::DifferenceCover => DifferenceCover.build_unchecked(text),
+        }
+    }
+}
+
+impl fmt::Display for SaAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SaAlgorithm {
+    type Err = String;
+
+    /// Parses the name of an algorithm returned by [`SuffixArrayBuilder::name`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SaAlgorithm::ALL
+            .iter()
+            .find(|algo| algo.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = SaAlgorithm::ALL.iter().map(|x| x.name()).collect();
+                format!(
+                    "unknown algorithm `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Linear congruential generator for reproducible random texts in tests.
+#[cfg(test)]
+struct Lcg(u64);
+
+#[cfg(test)]
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn text(&mut self, len: usize, sigma: u64) -> Vec<u32> {
+        (0..len).map(|_| (self.next() % sigma) as u32).collect()
+    }
+}
+
+#[cfg(test)]
+fn check_all_agree<T>(text: &[T])
+where
+    T: Ord + std::fmt::Debug,
+{
+    let ans: Vec<usize> = Naive.build(text).unwrap();
+    for algo in SaAlgorithm::ALL {
+        let sa: Vec<u32> = algo.build(text).unwrap();
+        let sa: Vec<usize> = sa.into_iter().map(|x| x as usize).collect();
+        assert_eq!(sa, ans, "{} on {:?}", algo, text);
+        let sa: Vec<u64> = algo.build(text).unwrap();
+        let sa: Vec<usize> = sa.into_iter().map(|x| x as usize).collect();
+        assert_eq!(sa, ans, "{} on {:?}", algo, text);
+    }
+}
+
+#[test]
+fn test_sa_algorithm() {
+    for algo in SaAlgorithm::ALL {
+        assert_eq!(algo.to_string().parse(), Ok(algo));
+    }
+    assert_eq!("sais".parse(), Ok(SaAlgorithm::InducedSorting));
+    assert_eq!("dc3".parse(), Ok(SaAlgorithm::DifferenceCover));
+    assert!("skew".parse::<SaAlgorithm>().is_err());
+
+    let text = br"bananaba$";
+    let ans: Vec<u32> = vec![8, 7, 5, 3, 1, 6, 0, 4, 2];
+    for algo in SaAlgorithm::ALL {
+        assert_eq!(algo.build(text), Ok(ans.clone()));
+    }
+}
+
+#[test]
+fn test_sa_builder_too_long() {
+    // The length is checked before the construction, and `()` takes no memory.
+    let max = u32::MAX as usize - 1;
+    let text: Vec<()> = vec![(); max + 1];
+    assert_eq!(
+        SaAlgorithm::Naive.build::<_, u32>(&text),
+        Err(SaError::TooLong { len: max + 1, max })
+    );
+}
+
+#[test]
+fn test_sa_builder_differential() {
+    use crate::fib::fibstr_ab;
+
+    // Adversarial inputs.
+    check_all_agree::<u8>(&[]);
+    check_all_agree(br"a");
+    check_all_agree(&[b'a'; 100]);
+    check_all_agree(&br"ab".repeat(50));
+    check_all_agree(&br"aab".repeat(33));
+    check_all_agree(&fibstr_ab(12));
+    let desc: Vec<u32> = (0..100).rev().collect();
+    check_all_agree(&desc);
+    let thue_morse: Vec<u32> = (0..256u32).map(|i| i.count_ones() % 2).collect();
+    check_all_agree(&thue_morse);
+    check_all_agree(&[u64::MAX, 0, u64::MAX, u64::MAX, 0, 1 << 40]);
+    check_all_agree(&["to", "be", "or", "not", "to", "be"]);
+
+    // Random inputs over small and large alphabets.
+    let mut rng = Lcg(0x2545f4914f6cdd1d);
+    for sigma in [1, 2, 3, 4, 26, 1000, 1 << 30] {
+        for _ in 0..50 {
+            let len = (rng.next() % 200) as usize;
+            check_all_agree(&rng.text(len, sigma));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! DC3 (skew) algorithm of Kärkkäinen and Sanders (2003).
+
+use super::misc::SaIndex;
+use super::sais::compact_alphabet;
+
+/// Make a suffix array by the DC3 algorithm in $O(n)$ time after reducing the alphabet.
+///
+/// Suffixes beginning at positions `i mod 3 != 0` are sorted recursively,
+/// then the other suffixes are sorted by radix sort using their ranks,
+/// and both are merged.
+pub fn make_sa_dc3<T, I>(text: &[T]) -> Vec<I>
+where
+    T: Ord,
+    I: SaIndex,
+{
+    let n = text.len();
+    if n <= 1 {
+        return (0..n).map(I::from_usize).collect();
+    }
+    // Characters are in `[1, sigma]` so that `0` can pad the text.
+    let ranks: Vec<usize> = compact_alphabet(text);
+    let sigma = ranks.iter().max().unwrap() + 1;
+    let mut s: Vec<usize> = ranks.into_iter().map(|c| c + 1).collect();
+    s.extend([0; 3]);
+    let mut sa = vec![0; n];
+    dc3(&s, &mut sa, n, sigma);
+    sa.into_iter().map(I::from_usize).collect()
+}
+
+/// Stably sorts `src` by `key` into `dst`, where keys are in `[0, k]`.
+fn radix_pass<F>(src: &[usize], dst: &mut [usize], key: F, k: usize)
+where
+    F: Fn(usize) -> usize,
+{
+    let mut cnt = vec![0; k + 1];
+    src.iter().for_each(|&x| cnt[key(x)] += 1);
+    let mut sum = 0;
+    for c in cnt.iter_mut() {
+        (sum, *c) = (sum + *c, sum);
+    }
+    for &x in src {
+        dst[cnt[key(x)]] = x;
+        cnt[key(x)] += 1;
+    }
+}
+
+#[inline(always)]
+fn leq2(a1: usize, a2: usize, b1: usize, b2: usize) -> bool {
+    (a1, a2) <= (b1, b2)
+}
+
+#[inline(always)]
+fn leq3(a1: usize, a2: usize, a3: usize, b1: usize, b2: usize, b3: usize) -> bool {
+    (a1, a2, a3) <= (b1, b2, b3)
+}
+
+/// Sorts the suffixes of `s[..n]` over `[1, sigma]` into `sa`, where `s[n..n+3]` are `0`s.
+fn dc3(s: &[usize], sa: &mut [usize], n: usize, sigma: usize) {
+    let n0 = n.div_ceil(3);
+    let n1 = (n + 1) / 3;
+    let n2 = n / 3;
+    let n02 = n0 + n2;
+    let mut s12 = vec![0; n02 + 3];
+    let mut sa12 = vec![0; n02 + 3];
+
+    // Sorts the triples of positions `i mod 3 != 0`.
+    // If `n mod 3 = 1`, a dummy triple at `n` is added so that the last mod-1 suffix is followed by a mod-2 one.
+    for (j, i) in (0..n + (n0 - n1)).filter(|i| i % 3 != 0).enumerate() {
+        s12[j] = i;
+    }
+    radix_pass(&s12[..n02], &mut sa12, |x| s[x + 2], sigma);
+    radix_pass(&sa12[..n02], &mut s12, |x| s[x + 1], sigma);
+    radix_pass(&s12[..n02], &mut sa12, |x| s[x], sigma);
+
+    // Names the triples, storing mod-1 positions followed by mod-2 positions.
+    let mut name = 0;
+    let mut prev = None;
+    for &p in sa12[..n02].iter() {
+        let triple = (s[p], s[p + 1], s[p + 2]);
+        if prev != Some(triple) {
+            name += 1;
+            prev = Some(triple);
+        }
+        if p % 3 == 1 {
+            s12[p / 3] = name;
+        } else {
+            s12[p / 3 + n0] = name;
+        }
+    }
+
+    if name < n02 {
+        // Names are not unique, so sorts the mod-1/2 suffixes recursively.
+        dc3(&s12, &mut sa12, n02, name);
+        for i in 0..n02 {
+            s12[sa12[i]] = i + 1;
+        }
+    } else {
+        for i in 0..n02 {
+            sa12[s12[i] - 1] = i;
+        }
+    }
+
+    // Sorts the mod-0 suffixes by their first characters and the ranks of the following mod-1 suffixes.
+    let s0: Vec<usize> = sa12[..n02]
+        .iter()
+        .filter(|&&x| x < n0)
+        .map(|&x| 3 * x)
+        .collect();
+    let mut sa0 = vec![0; n0];
+    radix_pass(&s0, &mut sa0, |x| s[x], sigma);
+
+    // Merges the mod-0 suffixes and the mod-1/2 suffixes, skipping the dummy.
+    let pos12 = |t: usize| {
+        if sa12[t] < n0 {
+            sa12[t] * 3 + 1
+        } else {
+            (sa12[t] - n0) * 3 + 2
+        }
+    };
+    let (mut p, mut t) = (0, n0 - n1);
+    let mut k = 0;
+    while k < n {
+        let i = pos12(t);
+        let j = sa0[p];
+        let is12 = if sa12[t] < n0 {
+            leq2(s[i], s12[sa12[t] + n0], s[j], s12[j / 3])
+        } else {
+            leq3(
+                s[i],
+                s[i + 1],
+                s12[sa12[t] - n0 + 1],
+                s[j],
+                s[j + 1],
+                s12[j / 3 + n0],
+            )
+        };
+        if is12 {
+            sa[k] = i;
+            t += 1;
+            if t == n02 {
+                for &x in sa0[p..].iter() {
+                    k += 1;
+                    sa[k] = x;
+                }
+                break;
+            }
+        } else {
+            sa[k] = j;
+            p += 1;
+            if p == n0 {
+                for t in t..n02 {
+                    k += 1;
+                    sa[k] = pos12(t);
+                }
+                break;
+            }
+        }
+        k += 1;
+    }
+}
+
+#[test]
+fn test_sa_dc3() {
+    use super::sanaive::make_sa_naive;
+    use crate::commons::enum_strs_len_leq;
+    use crate::fib::fibstr_ab;
+
+    let text = br"bananaba$";
+    let sa: Vec<u32> = make_sa_dc3(text);
+    assert_eq!(sa, vec![8, 7, 5, 3, 1, 6, 0, 4, 2]);
+
+    for text in enum_strs_len_leq(br"abc", 7) {
+        let sa: Vec<usize> = make_sa_dc3(&text);
+        assert_eq!(sa, make_sa_naive::<_, usize>(&text), "{:?}", text);
+    }
+    let text = fibstr_ab(15);
+    let sa: Vec<u64> = make_sa_dc3(&text);
+    assert_eq!(sa, make_sa_naive::<_, u64>(&text));
+}
@@ -1,6 +1,67 @@
-pub fn make_isa(sa: &[usize]) -> Vec<usize> {
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+use super::sais::IntoUsize;
+
+/// Integer types for the values of suffix arrays and their related arrays.
+///
+/// Callers choose the trade-off between memory and the maximum length of texts,
+/// e.g., `u32` for texts shorter than 4 GiB, and `u64` or `usize` for longer ones.
+pub trait SaIndex:
+    Copy
+    + Ord
+    + Hash
+    + Debug
+    + Default
+    + IntoUsize
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+
+    fn from_usize(x: usize) -> Self;
+}
+
+macro_rules! sa_index {
+    ($($type:ident),*) => {
+        $(impl SaIndex for $type {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MAX: Self = $type::MAX;
+
+            #[inline(always)]
+            fn from_usize(x: usize) -> Self {
+                debug_assert!(x as u128 <= $type::MAX as u128);
+                x as $type
+            }
+        })*
+    };
+}
+
+sa_index!(u32, u64, usize);
+
+/// Returns the inverse suffix array.
+pub fn make_isa<I>(sa: &[I]) -> Vec<I>
+where
+    I: SaIndex,
+{
     let n = sa.len();
-    let mut isa = vec![0; n];
-    sa.iter().for_each(|&i| isa[sa[i]] = i);
+    let mut isa = vec![I::ZERO; n];
+    sa.iter()
+        .enumerate()
+        .for_each(|(i, &x)| isa[x.into_usize()] = I::from_usize(i));
     isa
 }
+
+#[test]
+fn test_isa() {
+    let sa: Vec<u32> = vec![5, 3, 1, 0, 4, 2];
+    assert_eq!(make_isa(&sa), vec![3, 2, 5, 1, 4, 0]);
+}
@@ -2,7 +2,9 @@
 
 use std::{cmp::max, fmt::Debug};
 
-const EMPTY: u32 = u32::MAX;
+use super::misc::SaIndex;
+
+// `I::MAX` represents an empty slot of a suffix array.
 
 pub trait IntoUsize {
     #[allow(clippy::wrong_self_convention)]
@@ -20,7 +22,7 @@ macro_rules! into_usize {
     };
 }
 
-into_usize!(u8, u16, u32, u64);
+into_usize!(u8, u16, u32, u64, usize);
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 enum Type {
@@ -55,14 +57,15 @@ where
 /// If `head=true/false`, `bkt[c]` indicates the head/tail of the interval.
 /// More precisely, if `head=true`, `bkt[c]` is the number of characters less than `c`,
 /// if `head=false`, it is the number of characters less than or equal to `c`.
-fn make_bkta<T>(text: &[T], bkt: &mut [u32], head: bool)
+fn make_bkta<T, I>(text: &[T], bkt: &mut [I], head: bool)
 where
     T: Ord + IntoUsize,
+    I: SaIndex,
 {
-    bkt.iter_mut().for_each(|x| *x = 0);
-    text.iter().for_each(|c| bkt[c.into_usize()] += 1);
+    bkt.iter_mut().for_each(|x| *x = I::ZERO);
+    text.iter().for_each(|c| bkt[c.into_usize()] += I::ONE);
     if head {
-        let mut sum = 0;
+        let mut sum = I::ZERO;
         (0..bkt.len()).for_each(|i| {
             (sum, bkt[i]) = (sum + bkt[i], sum);
         });
@@ -74,74 +77,214 @@ where
 }
 
 /// Induce and sort L-type suffixes.
-fn induce_lsuf<T>(text: &[T], sa: &mut [u32], bkt: &mut [u32], types: &mut [Type])
+fn induce_lsuf<T, I>(text: &[T], sa: &mut [I], bkt: &mut [I], types: &mut [Type])
 where
     T: Ord + IntoUsize,
+    I: SaIndex,
 {
     make_bkta(text, bkt, true);
     {
         // The last suffix must be L-type suffix and it is not induced by any suffixes.
         // We put it in the head of its corresponding suffix interval.
         let last_c = text[text.len() - 1].into_usize();
-        sa[bkt[last_c] as usize] = (text.len() - 1) as u32;
-        bkt[last_c] += 1;
+        sa[bkt[last_c].into_usize()] = I::from_usize(text.len() - 1);
+        bkt[last_c] += I::ONE;
     }
 
     for i in 0..sa.len() {
-        let sufi = sa[i];
-        if sa[i] != EMPTY && sufi > 0 && types[(sufi - 1) as usize] == Type::TypeL {
-            let c = text[(sufi - 1) as usize].into_usize();
-            sa[bkt[c] as usize] = sufi - 1;
-            bkt[c] += 1;
+        let sufi = sa[i].into_usize();
+        if sa[i] != I::MAX && sufi > 0 && types[sufi - 1] == Type::TypeL {
+            let c = text[sufi - 1].into_usize();
+            sa[bkt[c].into_usize()] = I::from_usize(sufi - 1);
+            bkt[c] += I::ONE;
         }
     }
 }
 
 /// Induce and sort S-type suffixes.
-fn induce_ssuf<T>(text: &[T], sa: &mut [u32], bkt: &mut [u32], types: &mut [Type])
+fn induce_ssuf<T, I>(text: &[T], sa: &mut [I], bkt: &mut [I], types: &mut [Type])
 where
     T: Ord + IntoUsize,
+    I: SaIndex,
 {
     make_bkta(text, bkt, false);
     for i in (0..sa.len()).rev() {
-        let sufi = sa[i];
-        if sa[i] != EMPTY && sufi > 0 && types[(sufi - 1) as usize] == Type::TypeS {
-            let c = text[(sufi - 1) as usize].into_usize();
-            bkt[c] -= 1;
-            sa[bkt[c] as usize] = sufi - 1;
+        let sufi = sa[i].into_usize();
+        if sa[i] != I::MAX && sufi > 0 && types[sufi - 1] == Type::TypeS {
+            let c = text[sufi - 1].into_usize();
+            bkt[c] -= I::ONE;
+            sa[bkt[c].into_usize()] = I::from_usize(sufi - 1);
         }
     }
 }
 
 /// Make a suffix array.
-pub fn make_sa_induce<T>(text: &[T]) -> Vec<u32>
+///
+/// The index type `I` must be able to represent `text.len()`,
+/// e.g., `u32` for texts shorter than 4 GiB, `u64` or `usize` for longer ones.
+///
+/// Characters are used as indexes of the bucket array of size `max(256, text.len())` directly.
+/// If a character is out of the range, e.g., word IDs of a large vocabulary,
+/// the text is reduced to ranks of characters by [`make_sa_induce_ord`] in advance,
+/// so any character value is allowed.
+pub fn make_sa_induce<T, I>(text: &[T]) -> Vec<I>
 where
     T: Ord + IntoUsize,
+    I: SaIndex,
 {
     let n = text.len();
-    let mut sa = vec![0u32; n];
-    let mut bkt = vec![0u32; max(256, n)];
+    assert!(
+        n < I::MAX.into_usize(),
+        "the text of length {} is too long for the index type",
+        n
+    );
+    if n == 0 {
+        return vec![];
+    }
+    let bkt_len = max(256, n);
+    if text.iter().any(|c| c.into_usize() >= bkt_len) {
+        return make_sa_induce_ord(text);
+    }
+    let mut sa = vec![I::ZERO; n];
+    let mut bkt = vec![I::ZERO; bkt_len];
     let mut types = vec![Type::TypeL; n];
     sais(text, &mut sa, &mut bkt, &mut types);
     sa
 }
 
+/// Make a suffix array of a text over an arbitrary ordered alphabet.
+///
+/// The text is reduced to the ranks of its characters in the alphabet
+/// in $O(n \log \sigma)$ time and $n$ words of extra space before induced sorting,
+/// where $\sigma$ is the number of distinct characters.
+pub fn make_sa_induce_ord<T, I>(text: &[T]) -> Vec<I>
+where
+    T: Ord,
+    I: SaIndex,
+{
+    let ranks: Vec<I> = compact_alphabet(text);
+    make_sa_induce(&ranks)
+}
+
+/// Returns a text replacing each character with its rank in the alphabet of the text.
+///
+/// The order of the suffixes of the returned text equals one of the original text.
+pub fn compact_alphabet<T, I>(text: &[T]) -> Vec<I>
+where
+    T: Ord,
+    I: SaIndex,
+{
+    let mut alphabet: Vec<&T> = text.iter().collect();
+    alphabet.sort();
+    alphabet.dedup();
+    text.iter()
+        .map(|c| I::from_usize(alphabet.binary_search(&c).unwrap()))
+        .collect()
+}
+
+/// Make a suffix array and an LCP array at once.
+///
+/// The LCP array is computed during induced sorting following Fischer (2011):
+/// LCP values of LMS-suffixes are computed by a variant of the $\Phi$ method in $O(n)$ time,
+/// and the ones of the other suffixes are induced from them
+/// by minimum queries over the LCP values already computed.
+/// The minima are found by semi-dynamic range minimum queries in $O(1)$ amortized time,
+/// so it runs in $O(n)$ time.
+/// Besides the output and the bucket arrays, it uses $O(n/w \cdot \log n)$ words for the queries,
+/// where $w = 64$ is the word size.
+/// `lcp[i]` is the length of the longest common prefix of the suffixes `sa[i-1]` and `sa[i]`,
+/// and `lcp[0] = 0`.
+pub fn make_sa_lcp_induce<T, I>(text: &[T]) -> (Vec<I>, Vec<I>)
+where
+    T: Ord + IntoUsize,
+    I: SaIndex,
+{
+    let n = text.len();
+    assert!(
+        n < I::MAX.into_usize(),
+        "the text of length {} is too long for the index type",
+        n
+    );
+    if n == 0 {
+        return (vec![], vec![]);
+    }
+    let bkt_len = max(256, n);
+    if text.iter().any(|c| c.into_usize() >= bkt_len) {
+        let ranks: Vec<I> = compact_alphabet(text);
+        return make_sa_lcp_induce(&ranks);
+    }
+    let mut sa = vec![I::ZERO; n];
+    let mut lcp = vec![I::ZERO; n];
+    let mut bkt = vec![I::ZERO; bkt_len];
+    let mut types = vec![Type::TypeL; n];
+    let num_lms = sort_lms_suffixes(text, &mut sa, &mut bkt, &mut types);
+    make_lms_lcp(text, &mut sa, &mut lcp, num_lms, &types);
+
+    // Store sorted LMS-suffixes to the tail of each suffix interval with their LCP values.
+    // An entry moves to the right, so it never overwrites the entries not moved yet.
+    make_bkta(text, &mut bkt, false);
+    for i in (0..num_lms).rev() {
+        let suf = sa[i];
+        sa[i] = I::MAX;
+        let l = lcp[i];
+        lcp[i] = I::ZERO;
+        let c = text[suf.into_usize()].into_usize();
+        bkt[c] -= I::ONE;
+        sa[bkt[c].into_usize()] = suf;
+        lcp[bkt[c].into_usize()] = l;
+    }
+
+    let mut heads = vec![I::ZERO; bkt_len];
+    make_bkta(text, &mut heads, true);
+    induce_lsuf_lcp(text, &mut sa, &mut lcp, &mut bkt, &heads, &types);
+    induce_ssuf_lcp(text, &mut sa, &mut lcp, &mut bkt, &heads, &types);
+    (sa, lcp)
+}
+
 /// Make a suffix array by induced sorting algorithm.
-fn sais<T>(text: &[T], sa: &mut [u32], bkt: &mut [u32], types: &mut [Type])
+fn sais<T, I>(text: &[T], sa: &mut [I], bkt: &mut [I], types: &mut [Type])
 where
     T: Ord + IntoUsize,
+    I: SaIndex,
+{
+    let num_lms = sort_lms_suffixes(text, sa, bkt, types);
+
+    // Store sorted LMS-suffixes to the tail of each suffix interval with preserving the order.
+    make_bkta(text, bkt, false);
+    for i in (0..num_lms).rev() {
+        let suf = sa[i];
+        sa[i] = I::MAX;
+        let c = text[suf.into_usize()].into_usize();
+        bkt[c] -= I::ONE;
+        sa[bkt[c].into_usize()] = suf;
+    }
+
+    // Sort L-suffixes
+    induce_lsuf(text, sa, bkt, types);
+
+    // Sort S-suffixes
+    induce_ssuf(text, sa, bkt, types);
+}
+
+/// Sort LMS-suffixes, and store them in `sa[0..num_lms]`,
+/// where `num_lms` is the number of LMS-suffixes to be returned.
+/// The rest of `sa` contains `I::MAX`.
+fn sort_lms_suffixes<T, I>(text: &[T], sa: &mut [I], bkt: &mut [I], types: &mut [Type]) -> usize
+where
+    T: Ord + IntoUsize,
+    I: SaIndex,
 {
     let n = text.len();
     make_typea(text, types);
-    sa.iter_mut().for_each(|x| *x = EMPTY);
+    sa.iter_mut().for_each(|x| *x = I::MAX);
 
     // Store all LMS-substrings in the tail of each suffix interval.
     make_bkta(text, bkt, false);
     (0..n).for_each(|i| {
         if is_lms(types, i) {
             let c = text[i].into_usize();
-            bkt[c] -= 1;
-            sa[bkt[c] as usize] = i as u32;
+            bkt[c] -= I::ONE;
+            sa[bkt[c].into_usize()] = I::from_usize(i);
         }
     });
 
@@ -150,28 +293,28 @@ where
     induce_ssuf(text, sa, bkt, types);
 
     // Store LMS-substrings in the head of SA[0..nLms]
-    let mut num_lms: u32 = 0;
+    let mut num_lms = 0;
     for i in 0..n {
         let sufi = sa[i];
-        sa[i] = EMPTY;
-        // assert!(sufi != EMPTY);
-        if is_lms(types, sufi as usize) {
-            sa[num_lms as usize] = sufi;
+        sa[i] = I::MAX;
+        // assert!(sufi != I::MAX);
+        if is_lms(types, sufi.into_usize()) {
+            sa[num_lms] = sufi;
             num_lms += 1;
         }
     }
 
-    // Here, sa[0..num_lms] stores LMS-substring in sorted, and the rest contains `EMPTY`.
+    // Here, sa[0..num_lms] stores LMS-substring in sorted, and the rest contains `I::MAX`.
     // For an LMS-substring beginning at `i`, we compute its rank and store it in `sa[num_lms+sa[i]/2]`.
 
-    let mut num_distinct_lms: u32 = 0;
+    let mut num_distinct_lms = 0;
     if num_lms > 0 {
         num_distinct_lms = 1;
-        sa[(num_lms + sa[0] / 2) as usize] = 0;
+        sa[num_lms + sa[0].into_usize() / 2] = I::ZERO;
     }
-    for i in 1..(num_lms as usize) {
-        let mut sufi = sa[i] as usize;
-        let mut prev = sa[i - 1] as usize;
+    for i in 1..num_lms {
+        let mut sufi = sa[i].into_usize();
+        let mut prev = sa[i - 1].into_usize();
         if text[sufi] != text[prev] {
             num_distinct_lms += 1;
         } else {
@@ -190,16 +333,16 @@ where
             }
         }
 
-        sa[(num_lms + sa[i] / 2) as usize] = num_distinct_lms - 1;
+        sa[num_lms + sa[i].into_usize() / 2] = I::from_usize(num_distinct_lms - 1);
     }
 
     // Make a new text substituting the text with LMS-substrings by their ranks.
     // We store it in sa[num_lms..2*num_lms].
-    let mut text_end = num_lms as usize;
-    for i in num_lms as usize..n {
-        if sa[i] != EMPTY {
+    let mut text_end = num_lms;
+    for i in num_lms..n {
+        if sa[i] != I::MAX {
             let rank = sa[i];
-            sa[i] = EMPTY;
+            sa[i] = I::MAX;
             sa[text_end] = rank;
             text_end += 1;
         }
@@ -208,19 +351,14 @@ where
     // Make a suffix array of the new text.
     // Namely, we sort LMS-suffixes not LMS-substrings.
     let (front, _) = sa.split_at_mut(text_end);
-    let (sa2, text2) = front.split_at_mut(num_lms as usize);
+    let (sa2, text2) = front.split_at_mut(num_lms);
     assert_eq!(text2.len(), sa2.len());
     if num_distinct_lms != num_lms {
-        sais(
-            text2,
-            sa2,
-            &mut bkt[..num_lms as usize],
-            &mut types[..num_lms as usize],
-        );
+        sais(text2, sa2, &mut bkt[..num_lms], &mut types[..num_lms]);
         make_typea(text, types);
     } else {
         for (i, &c) in text2.iter().enumerate() {
-            sa2[c as usize] = i as u32;
+            sa2[c.into_usize()] = I::from_usize(i);
         }
     }
 
@@ -230,38 +368,298 @@ where
     let mut j = 0;
     for i in 0..n {
         if is_lms(types, i) {
-            text2[j] = i as u32;
+            text2[j] = I::from_usize(i);
             j += 1;
         }
     }
     for i in 0..sa2.len() {
-        sa2[i] = text2[sa2[i] as usize];
+        sa2[i] = text2[sa2[i].into_usize()];
     }
 
     // Clean up `sa[num_lms..]`.
-    // for i in num_lms as usize..sa.len() {
-    (num_lms as usize..(2 * num_lms as usize)).for_each(|i| {
-        sa[i] = EMPTY;
+    // for i in num_lms..sa.len() {
+    (num_lms..(2 * num_lms)).for_each(|i| {
+        sa[i] = I::MAX;
     });
+    num_lms
+}
 
-    // Store sorted LMS-suffixes to the tail of each suffix interval with preserving the order.
-    make_bkta(text, bkt, false);
-    for i in (0..num_lms as usize).rev() {
-        let suf = sa[i];
-        sa[i] = EMPTY;
-        let c = text[suf as usize].into_usize();
-        bkt[c] -= 1;
-        sa[bkt[c] as usize] = suf;
+/// Returns the length of the longest common prefix of `text[i..]` and `text[j..]`.
+fn lcp_naive<T>(text: &[T], i: usize, j: usize) -> usize
+where
+    T: Eq,
+{
+    text[i..]
+        .iter()
+        .zip(text[j..].iter())
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
+/// Computes the LCP values of the sorted LMS-suffixes in `sa[..num_lms]`,
+/// i.e., `lcp[k]` is the LCP value of the LMS-suffixes `sa[k-1]` and `sa[k]` for `0 < k < num_lms`,
+/// where `sa[num_lms..]` must be empty and is used as a working space.
+///
+/// Similar to the $\Phi$ method, the LCP value of the previous LMS-suffix `p'` in the text gives a lower bound:
+/// if `text[q'..]` precedes `text[p'..]` with the LCP value `l > p-p'`, and `q'+p-p'` is also an LMS position,
+/// then the LCP value of `p` is at least `l-(p-p')`.
+///
+/// Otherwise, the bound is reset to `0`, but this costs $O(n)$ time in total.
+/// Since `text[p-1] > text[p]` at an LMS position `p`, the position `q'+p-p' - 1` is L-type as well,
+/// so `q'+p-p'` is not an LMS position only if `text[p..p'+l]` is a run of a single character.
+/// The lost bound `l-(p-p')` is at most the length of the maximal run beginning at `p`,
+/// and the maximal runs beginning at distinct LMS positions are disjoint.
+/// Hence, the characters compared sum up to $O(n)$ as in the $\Phi$ method.
+fn make_lms_lcp<T, I>(text: &[T], sa: &mut [I], lcp: &mut [I], num_lms: usize, types: &[Type])
+where
+    T: Ord,
+    I: SaIndex,
+{
+    let n = text.len();
+    let (lms_sa, ranks) = sa.split_at_mut(num_lms);
+    // `ranks[p/2]` temporarily stores the rank of an LMS-suffix `p`,
+    // where it fits since LMS positions are at least two apart.
+    for (k, &p) in lms_sa.iter().enumerate() {
+        ranks[p.into_usize() / 2] = I::from_usize(k);
+    }
+    let mut prev: Option<(usize, usize, usize)> = None;
+    for p in (0..n).filter(|&p| is_lms(types, p)) {
+        let k = ranks[p / 2].into_usize();
+        ranks[p / 2] = I::MAX;
+        if k == 0 {
+            prev = None;
+            continue;
+        }
+        let q = lms_sa[k - 1].into_usize();
+        let mut l = match prev {
+            Some((p0, q0, l0)) if l0 > p - p0 && is_lms(types, q0 + p - p0) => l0 - (p - p0),
+            _ => 0,
+        };
+        l += lcp_naive(text, p + l, q + l);
+        lcp[k] = I::from_usize(l);
+        prev = Some((p, q, l));
     }
+}
 
-    // Sort L-suffixes
-    induce_lsuf(text, sa, bkt, types);
+/// Number of values in a block of [`SemiDynamicRmq`].
+const RMQ_BLOCK: usize = u64::BITS as usize;
 
-    // Sort S-suffixes
-    induce_ssuf(text, sa, bkt, types);
+/// Range minimum queries over values appended at increasing indexes,
+/// where a range must end at the last appended index.
+///
+/// The values are read by a given function `val` from their indexes, and must not change after appended.
+/// Indexes are divided into blocks of $w = 64$ indexes.
+/// Within a block, the indexes whose values are smaller than all the later ones in the block are kept in a bitmask,
+/// and the minima of complete blocks are stored in a sparse table.
+/// Appending takes $O(1)$ amortized time and $O(\log n)$ time per block,
+/// and a query takes $O(1)$ time, so they take $O(n)$ time in total for $\log n \le w$.
+struct SemiDynamicRmq {
+    /// Bitmask of the current block, i.e., the one of the last appended index.
+    cur: u64,
+    /// Final bitmasks of the complete blocks.
+    masks: Vec<u64>,
+    /// `table[k][b]` is the minimum of the blocks `b..b+2^k`, where `usize::MAX` is the one of an empty block.
+    table: Vec<Vec<usize>>,
 }
 
-// fn show<T>(text: &[T], sa: &[u32], types: &[Type], bkt: &[u32])
+impl SemiDynamicRmq {
+    fn new() -> Self {
+        Self {
+            cur: 0,
+            masks: vec![],
+            table: vec![vec![]],
+        }
+    }
+
+    /// Appends the value of index `idx`, which must be larger than the indexes appended so far.
+    fn push<F>(&mut self, idx: usize, val: F)
+    where
+        F: Fn(usize) -> usize,
+    {
+        while self.masks.len() < idx / RMQ_BLOCK {
+            self.complete_block(&val);
+        }
+        let base = self.masks.len() * RMQ_BLOCK;
+        let v = val(idx);
+        while self.cur != 0 {
+            let top = RMQ_BLOCK - 1 - self.cur.leading_zeros() as usize;
+            if val(base + top) < v {
+                break;
+            }
+            self.cur &= !(1 << top);
+        }
+        self.cur |= 1 << (idx % RMQ_BLOCK);
+    }
+
+    fn complete_block<F>(&mut self, val: F)
+    where
+        F: Fn(usize) -> usize,
+    {
+        let b = self.masks.len();
+        let min = match self.cur {
+            0 => usize::MAX,
+            mask => val(b * RMQ_BLOCK + mask.trailing_zeros() as usize),
+        };
+        self.masks.push(self.cur);
+        self.cur = 0;
+        self.table[0].push(min);
+        let len = b + 1;
+        for k in 1.. {
+            if len < 1 << k {
+                break;
+            }
+            if self.table.len() == k {
+                self.table.push(vec![]);
+            }
+            let (lo, hi) = (len - (1 << k), len - (1 << (k - 1)));
+            let min = self.table[k - 1][lo].min(self.table[k - 1][hi]);
+            self.table[k].push(min);
+        }
+    }
+
+    /// Returns the minimum value of the indexes from `beg` to the last appended one.
+    fn min_from<F>(&self, beg: usize, val: F) -> usize
+    where
+        F: Fn(usize) -> usize,
+    {
+        let (b, cb) = (beg / RMQ_BLOCK, self.masks.len());
+        let first = |mask: u64, b: usize| val(b * RMQ_BLOCK + mask.trailing_zeros() as usize);
+        let from = !0u64 << (beg % RMQ_BLOCK);
+        if b == cb {
+            return first(self.cur & from, cb);
+        }
+        let mut res = first(self.cur, cb);
+        if self.masks[b] & from != 0 {
+            res = res.min(first(self.masks[b] & from, b));
+        }
+        if b + 1 < cb {
+            let (lo, hi) = (b + 1, cb);
+            let k = (hi - lo).ilog2() as usize;
+            res = res.min(self.table[k][lo]).min(self.table[k][hi - (1 << k)]);
+        }
+        res
+    }
+}
+
+/// Induce and sort L-type suffixes with computing their LCP values.
+///
+/// `lcp[i]` of an entry is the LCP value with the preceding entry in the current `sa`.
+fn induce_lsuf_lcp<T, I>(
+    text: &[T],
+    sa: &mut [I],
+    lcp: &mut [I],
+    bkt: &mut [I],
+    heads: &[I],
+    types: &[Type],
+) where
+    T: Ord + IntoUsize,
+    I: SaIndex,
+{
+    let n = text.len();
+    // `last[c]` is the position of `sa` that induced the last L-type suffix in bucket `c`.
+    let mut last = vec![usize::MAX; bkt.len()];
+    let mut lms_seen = vec![false; bkt.len()];
+    bkt.copy_from_slice(heads);
+    {
+        // The last suffix is a single character, and it is not induced by any suffixes.
+        let last_c = text[n - 1].into_usize();
+        sa[bkt[last_c].into_usize()] = I::from_usize(n - 1);
+        lcp[bkt[last_c].into_usize()] = I::ZERO;
+        bkt[last_c] += I::ONE;
+        last[last_c] = n;
+    }
+
+    let mut mins = SemiDynamicRmq::new();
+    for i in 0..sa.len() {
+        if sa[i] == I::MAX {
+            continue;
+        }
+        let sufi = sa[i].into_usize();
+        if types[sufi] == Type::TypeS {
+            // The LCP value of the first LMS-suffix in a bucket is computed with the last L-type suffix in the bucket.
+            let c = text[sufi].into_usize();
+            if !lms_seen[c] {
+                lms_seen[c] = true;
+                lcp[i] = if bkt[c] > heads[c] {
+                    I::from_usize(lcp_naive(
+                        text,
+                        sa[bkt[c].into_usize() - 1].into_usize(),
+                        sufi,
+                    ))
+                } else {
+                    I::ZERO
+                };
+            }
+        }
+        mins.push(i, |k| lcp[k].into_usize());
+
+        if sufi > 0 && types[sufi - 1] == Type::TypeL {
+            let c = text[sufi - 1].into_usize();
+            let j = bkt[c].into_usize();
+            sa[j] = I::from_usize(sufi - 1);
+            lcp[j] = I::from_usize(match last[c] {
+                usize::MAX => 0,
+                x if x == n => 1,
+                x => 1 + mins.min_from(x + 1, |k| lcp[k].into_usize()),
+            });
+            bkt[c] += I::ONE;
+            last[c] = i;
+        }
+    }
+}
+
+/// Induce and sort S-type suffixes with computing their LCP values.
+fn induce_ssuf_lcp<T, I>(
+    text: &[T],
+    sa: &mut [I],
+    lcp: &mut [I],
+    bkt: &mut [I],
+    heads: &[I],
+    types: &[Type],
+) where
+    T: Ord + IntoUsize,
+    I: SaIndex,
+{
+    let n = text.len();
+    // `last[c]` is the position of `sa` that induced the last S-type suffix in bucket `c`.
+    let mut last = vec![usize::MAX; bkt.len()];
+    make_bkta(text, bkt, false);
+
+    // The values are appended in the descending order of positions, so position `i` is appended as index `n-1-i`.
+    let mut mins = SemiDynamicRmq::new();
+    for i in (0..n).rev() {
+        // Here, `sa[i..]` is fixed, and so is the LCP value of `sa[i]` and `sa[i+1]`.
+        if i + 1 < n {
+            let suf = sa[i + 1].into_usize();
+            let c = text[suf].into_usize();
+            if types[suf] == Type::TypeS && bkt[c].into_usize() == i + 1 {
+                // The first S-type suffix in a bucket.
+                lcp[i + 1] = if heads[c].into_usize() == i + 1 {
+                    I::ZERO
+                } else {
+                    I::from_usize(lcp_naive(text, sa[i].into_usize(), suf))
+                };
+            }
+            mins.push(n - 2 - i, |k| lcp[n - 1 - k].into_usize());
+        }
+
+        let sufi = sa[i].into_usize();
+        if sufi > 0 && types[sufi - 1] == Type::TypeS {
+            let c = text[sufi - 1].into_usize();
+            bkt[c] -= I::ONE;
+            let j = bkt[c].into_usize();
+            sa[j] = I::from_usize(sufi - 1);
+            if last[c] != usize::MAX {
+                let min = mins.min_from(n - 1 - last[c], |k| lcp[n - 1 - k].into_usize());
+                lcp[j + 1] = I::from_usize(1 + min);
+            }
+            last[c] = i;
+        }
+    }
+    lcp[0] = I::ZERO;
+}
+
+// fn show<T, I>(text: &[T], sa: &[I], types: &[Type], bkt: &[I])
 // where
 //     T: Ord + IntoUsize,
 // {
@@ -271,3 +669,65 @@ where
 //     println!("types={:?}", types);
 //     println!("bkt={:?}", bkt);
 // }
+
+#[test]
+fn test_sa_induce() {
+    use super::sanaive::make_sa_naive;
+    use crate::fib::fibstr_ab;
+
+    let texts = vec![
+        br"a".to_vec(),
+        br"bananaba$".to_vec(),
+        br"mississippi".to_vec(),
+        br"aaaaaaaaaa".to_vec(),
+        fibstr_ab(10),
+    ];
+    for text in texts {
+        let ans: Vec<usize> = make_sa_naive(&text);
+        let sa32: Vec<u32> = make_sa_induce(&text);
+        let sa64: Vec<u64> = make_sa_induce(&text);
+        let sa: Vec<usize> = make_sa_induce(&text);
+        assert_eq!(ans, sa);
+        assert!(ans.iter().zip(sa32).all(|(&x, y)| x == y as usize));
+        assert!(ans.iter().zip(sa64).all(|(&x, y)| x == y as usize));
+    }
+}
+
+#[test]
+fn test_sa_induce_large_alphabet() {
+    use super::sanaive::make_sa_naive;
+
+    let text: Vec<u32> = vec![3_000_000, 7, 3_000_000, 7, 300, 3_000_000, 7, 1 << 31];
+    let ans: Vec<usize> = make_sa_naive(&text);
+    let sa: Vec<usize> = make_sa_induce(&text);
+    assert_eq!(ans, sa);
+
+    let text: Vec<u64> = vec![u64::MAX, 1, u64::MAX - 1, 1, u64::MAX];
+    let ans: Vec<u32> = make_sa_naive(&text);
+    let sa: Vec<u32> = make_sa_induce(&text);
+    assert_eq!(ans, sa);
+
+    let text = ["to", "be", "or", "not", "to", "be"];
+    let ans: Vec<usize> = make_sa_naive(&text);
+    let sa: Vec<usize> = make_sa_induce_ord(&text);
+    assert_eq!(ans, sa);
+}
+
+#[test]
+fn test_semi_dynamic_rmq() {
+    // Indexes with gaps, whose values repeat with a period longer than a block.
+    let idxs: Vec<usize> = (0..1000).filter(|i| i % 7 != 3).collect();
+    let val = |i: usize| (i * 37) % 101;
+    let mut rmq = SemiDynamicRmq::new();
+    for (k, &i) in idxs.iter().enumerate() {
+        rmq.push(i, val);
+        for &beg in idxs[..=k].iter().step_by(5) {
+            let ans = idxs[..=k]
+                .iter()
+                .filter(|&&j| j >= beg)
+                .map(|&j| val(j))
+                .min();
+            assert_eq!(rmq.min_from(beg, val), ans.unwrap());
+        }
+    }
+}
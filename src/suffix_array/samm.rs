@@ -1,44 +1,111 @@
-use std::{
-    cell::{Cell, RefCell},
-    cmp::Ordering,
-    rc::Rc,
-};
+use super::misc::SaIndex;
+use super::sais::compact_alphabet;
 
-pub fn make_sa_mm(text: &[u8]) -> Vec<u32> {
+/// Make a suffix array by prefix doubling of Manber and Myers.
+///
+/// In the round for `h`, suffixes are sorted by their prefixes of length `2h`
+/// as the pairs of the ranks of their prefixes and the ones `h` positions later,
+/// by radix sort in $O(n)$ time.
+/// The construction stops as soon as all the ranks are distinct,
+/// so it takes $O(n \log L)$ time, where $L$ is the length of the longest repeat.
+pub fn make_sa_mm<T, I>(text: &[T]) -> Vec<I>
+where
+    T: Ord,
+    I: SaIndex,
+{
     let n = text.len();
-    let mut sa: Vec<u32> = (0..n).into_iter().map(|x| x as u32).collect();
-    let rank: Vec<u32> = text.iter().map(|&x| x as u32).collect();
-    let rank = Rc::new(RefCell::new(rank));
-    let len: Rc<Cell<usize>> = Rc::new(Cell::new(1 as usize));
-    let compare_suf = |i: &u32, j: &u32| {
-        let (i, j) = (*i as usize, *j as usize);
-        let l = len.get();
-        let rank = rank.borrow();
-        if rank[i] != rank[j] {
-            rank[i].cmp(&rank[j])
-        } else if i + l >= n {
-            Ordering::Less
-        } else if j + l >= n {
-            Ordering::Greater
-        } else {
-            rank[i + l].cmp(&rank[j + l])
+    if n == 0 {
+        return vec![];
+    }
+    let mut rank: Vec<I> = compact_alphabet(text);
+    let mut sa = vec![I::ZERO; n];
+    let mut tmp = vec![I::ZERO; n];
+    let mut cnt = vec![0; n];
+
+    // Sorts suffixes by their first characters.
+    (0..n).for_each(|i| tmp[i] = I::from_usize(i));
+    counting_sort(&tmp, &mut sa, &rank, &mut cnt);
+    let mut num_ranks = rank.iter().max().unwrap().into_usize() + 1;
+
+    let mut h = 1;
+    while num_ranks < n {
+        // Sorts suffixes by the ranks `h` positions later,
+        // where suffixes shorter than `h` come first.
+        let mut k = 0;
+        for i in n - h..n {
+            tmp[k] = I::from_usize(i);
+            k += 1;
         }
-    };
-    while len.get() < n {
-        sa.sort_by(compare_suf);
-        let mut tmp = vec![0u32; n];
-        tmp[sa[0] as usize] = 0;
-        for i in 1..n {
-            tmp[sa[i] as usize] = tmp[sa[i - 1] as usize]
-                + if compare_suf(&sa[i - 1], &sa[i]) == Ordering::Less {
-                    1
-                } else {
-                    0
-                };
+        for &i in sa.iter() {
+            if i.into_usize() >= h {
+                tmp[k] = i - I::from_usize(h);
+                k += 1;
+            }
         }
-        (0..n).for_each(|i| rank.borrow_mut()[i] = tmp[i]);
+        // Then, stably sorts them by their own ranks.
+        counting_sort(&tmp, &mut sa, &rank, &mut cnt);
 
-        len.set(len.get() * 2);
+        // Ranks of the prefixes of length `2h`, stored in `tmp` temporarily.
+        let key = |i: usize| {
+            let second = if i + h < n {
+                rank[i + h].into_usize() + 1
+            } else {
+                0
+            };
+            (rank[i], second)
+        };
+        num_ranks = 1;
+        tmp[sa[0].into_usize()] = I::ZERO;
+        for k in 1..n {
+            let (prev, cur) = (sa[k - 1].into_usize(), sa[k].into_usize());
+            if key(prev) != key(cur) {
+                num_ranks += 1;
+            }
+            tmp[cur] = I::from_usize(num_ranks - 1);
+        }
+        std::mem::swap(&mut rank, &mut tmp);
+        h *= 2;
     }
     sa
 }
+
+/// Stably sorts positions `src` by `rank` into `dst`, where ranks are less than `cnt.len()`.
+fn counting_sort<I>(src: &[I], dst: &mut [I], rank: &[I], cnt: &mut [usize])
+where
+    I: SaIndex,
+{
+    cnt.iter_mut().for_each(|x| *x = 0);
+    src.iter()
+        .for_each(|&i| cnt[rank[i.into_usize()].into_usize()] += 1);
+    let mut sum = 0;
+    for c in cnt.iter_mut() {
+        (sum, *c) = (sum + *c, sum);
+    }
+    for &i in src {
+        let r = rank[i.into_usize()].into_usize();
+        dst[cnt[r]] = i;
+        cnt[r] += 1;
+    }
+}
+
+#[test]
+fn test_sa_mm() {
+    use super::sanaive::make_sa_naive;
+    use crate::commons::enum_strs_len_leq;
+    use crate::fib::fibstr_ab;
+
+    let text = br"bananaba$";
+    let sa: Vec<u32> = make_sa_mm(text);
+    assert_eq!(sa, vec![8, 7, 5, 3, 1, 6, 0, 4, 2]);
+
+    for text in enum_strs_len_leq(br"abc", 7) {
+        let sa: Vec<usize> = make_sa_mm(&text);
+        assert_eq!(sa, make_sa_naive::<_, usize>(&text), "{:?}", text);
+    }
+    let text = fibstr_ab(15);
+    let sa: Vec<u64> = make_sa_mm(&text);
+    assert_eq!(sa, make_sa_naive::<_, u64>(&text));
+    let text: Vec<u64> = vec![u64::MAX, 1 << 40, u64::MAX, 0, u64::MAX, 1 << 40];
+    let sa: Vec<u32> = make_sa_mm(&text);
+    assert_eq!(sa, make_sa_naive::<_, u32>(&text));
+}
@@ -1,26 +1,30 @@
+use super::misc::SaIndex;
+
 /// Returns suffix array.
-pub fn make_sa<T>(text: &[T]) -> Vec<usize>
+pub fn make_sa<T, I>(text: &[T]) -> Vec<I>
 where
     T: Ord,
+    I: SaIndex,
 {
     make_sa_naive(text)
 }
 
 /// Returns suffix array using a naive algorithm.
-pub fn make_sa_naive<T>(text: &[T]) -> Vec<usize>
+pub fn make_sa_naive<T, I>(text: &[T]) -> Vec<I>
 where
     T: Ord,
+    I: SaIndex,
 {
     let n = text.len();
-    let mut sa: Vec<usize> = (0..n).collect();
-    sa.sort_by(|&x, &y| text[x as usize..].cmp(&text[y as usize..]));
+    let mut sa: Vec<I> = (0..n).map(I::from_usize).collect();
+    sa.sort_by(|&x, &y| text[x.into_usize()..].cmp(&text[y.into_usize()..]));
     sa
 }
 
 #[test]
 fn test_sa() {
     let text = br"bananaba$";
-    let sa = make_sa(text);
+    let sa: Vec<usize> = make_sa(text);
     let ans = vec![8, 7, 5, 3, 1, 6, 0, 4, 2];
     assert_eq!(ans, sa);
 }
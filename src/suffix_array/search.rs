@@ -0,0 +1,235 @@
+use std::{cmp::min, ops::Range};
+
+use super::misc::SaIndex;
+
+/// Pattern search on a suffix array.
+///
+/// Without an LCP array, the binary search skips the prefix of the pattern
+/// matched with both ends of the current interval (the mlr heuristic),
+/// which works well in practice but takes $O(m \log n)$ time in the worst case for a pattern of length $m$.
+/// With an LCP array, it uses the LCP values between the ends and the middle of each interval
+/// as Manber and Myers (1993) did, and takes $O(m + \log n)$ time.
+pub struct SuffixArraySearch<'a, T, I> {
+    text: &'a [T],
+    sa: &'a [I],
+    /// `llcp[m]`/`rlcp[m]` are the LCP values between the middle `m` and the left/right end of the interval
+    /// whose middle is `m` in the binary search.
+    /// Positions are shifted by one, where `0` and `n+1` are the virtual smallest and largest suffixes.
+    lcp_tree: Option<(Vec<I>, Vec<I>)>,
+}
+
+impl<'a, T, I> SuffixArraySearch<'a, T, I>
+where
+    T: Ord,
+    I: SaIndex,
+{
+    /// Creates a searcher of a text with its suffix array.
+    pub fn new(text: &'a [T], sa: &'a [I]) -> Self {
+        debug_assert_eq!(text.len(), sa.len());
+        Self {
+            text,
+            sa,
+            lcp_tree: None,
+        }
+    }
+
+    /// Creates a searcher of a text with its suffix array and its LCP array.
+    pub fn with_lcp(text: &'a [T], sa: &'a [I], lcp: &[I]) -> Self {
+        debug_assert_eq!(text.len(), sa.len());
+        debug_assert_eq!(text.len(), lcp.len());
+        let n = text.len();
+        let mut llcp = vec![I::ZERO; n + 2];
+        let mut rlcp = vec![I::ZERO; n + 2];
+        build_lcp_tree(lcp, 0, n + 1, &mut llcp, &mut rlcp);
+        Self {
+            text,
+            sa,
+            lcp_tree: Some((llcp, rlcp)),
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the `i`-th smallest suffix.
+    #[inline(always)]
+    fn suffix(&self, i: usize) -> &[T] {
+        &self.text[self.sa[i].into_usize()..]
+    }
+
+    /// Returns the lowest position of the suffix array whose suffix is greater than or equal to `pattern`,
+    /// where suffixes are truncated to the length of `pattern` if `upper=false`.
+    /// If `upper=true`, it returns the lowest position whose suffix is greater than `pattern`.
+    fn bound(&self, pattern: &[T], upper: bool) -> usize {
+        let (mut lo, mut hi) = (0, self.len() + 1);
+        let (mut l, mut r) = (0, 0);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            let k = match &self.lcp_tree {
+                Some((llcp, rlcp)) => {
+                    if l >= r {
+                        let x = llcp[mid].into_usize();
+                        if x > l {
+                            lo = mid;
+                            continue;
+                        } else if x < l {
+                            (hi, r) = (mid, x);
+                            continue;
+                        }
+                        l
+                    } else {
+                        let x = rlcp[mid].into_usize();
+                        if x > r {
+                            hi = mid;
+                            continue;
+                        } else if x < r {
+                            (lo, l) = (mid, x);
+                            continue;
+                        }
+                        r
+                    }
+                }
+                None => min(l, r),
+            };
+            let suf = self.suffix(mid - 1);
+            let k = k + pattern[k..]
+                .iter()
+                .zip(suf[k..].iter())
+                .take_while(|(x, y)| x == y)
+                .count();
+            let go_right = if k == pattern.len() {
+                upper
+            } else {
+                k == suf.len() || suf[k] < pattern[k]
+            };
+            if go_right {
+                (lo, l) = (mid, k);
+            } else {
+                (hi, r) = (mid, k);
+            }
+        }
+        hi - 1
+    }
+
+    /// Returns the interval of the suffix array whose suffixes begin with a given pattern.
+    pub fn range(&self, pattern: &[T]) -> Range<usize> {
+        let beg = self.bound(pattern, false);
+        let end = self.bound(pattern, true);
+        beg..end
+    }
+
+    /// Returns the number of occurrences of a given pattern.
+    pub fn count(&self, pattern: &[T]) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Returns the beginning positions of occurrences of a given pattern in ascending order.
+    pub fn locate(&self, pattern: &[T]) -> Vec<usize> {
+        let mut res: Vec<usize> = self
+            .range(pattern)
+            .map(|i| self.sa[i].into_usize())
+            .collect();
+        res.sort_unstable();
+        res
+    }
+
+    /// Finds the longest prefix of a given pattern that occurs in the text.
+    /// Returns the interval of the suffix array whose suffixes begin with the prefix, and its length.
+    pub fn longest_prefix_match(&self, pattern: &[T]) -> (Range<usize>, usize) {
+        let i = self.bound(pattern, false);
+        let lcp_with = |j: usize| {
+            self.suffix(j)
+                .iter()
+                .zip(pattern.iter())
+                .take_while(|(x, y)| x == y)
+                .count()
+        };
+        let mut len = 0;
+        if i > 0 {
+            len = lcp_with(i - 1);
+        }
+        if i < self.len() {
+            len = len.max(lcp_with(i));
+        }
+        (self.range(&pattern[..len]), len)
+    }
+}
+
+/// Computes `llcp` and `rlcp` for the interval `(lo, hi)`, and returns the LCP value of `lo` and `hi`,
+/// where positions are shifted by one.
+fn build_lcp_tree<I>(lcp: &[I], lo: usize, hi: usize, llcp: &mut [I], rlcp: &mut [I]) -> I
+where
+    I: SaIndex,
+{
+    if hi - lo == 1 {
+        return if lo == 0 || hi == lcp.len() + 1 {
+            I::ZERO
+        } else {
+            lcp[hi - 1]
+        };
+    }
+    let mid = (lo + hi) / 2;
+    llcp[mid] = build_lcp_tree(lcp, lo, mid, llcp, rlcp);
+    rlcp[mid] = build_lcp_tree(lcp, mid, hi, llcp, rlcp);
+    min(llcp[mid], rlcp[mid])
+}
+
+#[test]
+fn test_search() {
+    use super::sais::make_sa_induce;
+    use crate::lcp::make_lcpa;
+
+    let text = br"mississippi";
+    let sa: Vec<u32> = make_sa_induce(text);
+    let lcp = make_lcpa(text, &sa);
+    for search in [
+        SuffixArraySearch::new(text, &sa),
+        SuffixArraySearch::with_lcp(text, &sa, &lcp),
+    ] {
+        assert_eq!(search.range(br"i"), 0..4);
+        assert_eq!(search.count(br"ssi"), 2);
+        assert_eq!(search.count(br"x"), 0);
+        assert_eq!(search.count(br""), text.len());
+        assert_eq!(search.locate(br"issi"), vec![1, 4]);
+        assert_eq!(search.locate(br"mississippi"), vec![0]);
+        assert!(search.locate(br"mississippis").is_empty());
+        assert_eq!(search.longest_prefix_match(br"ssippix"), (9..10, 6));
+        assert_eq!(search.longest_prefix_match(br"sx"), (7..11, 1));
+        assert_eq!(search.longest_prefix_match(br"x"), (0..11, 0));
+    }
+}
+
+#[test]
+fn test_search_naive() {
+    use super::samm::make_sa_mm;
+    use super::sanaive::make_sa_naive;
+    use crate::commons::{locate_naive, test_patterns, test_texts};
+    use crate::lcp::make_lcpa;
+
+    for text in test_texts() {
+        let sa1: Vec<usize> = make_sa_naive(&text);
+        let sa2: Vec<u32> = make_sa_mm(&text);
+        let lcp1 = make_lcpa(&text, &sa1);
+        let lcp2 = make_lcpa(&text, &sa2);
+        let search1 = SuffixArraySearch::with_lcp(&text, &sa1, &lcp1);
+        let search2 = SuffixArraySearch::with_lcp(&text, &sa2, &lcp2);
+        let search3 = SuffixArraySearch::new(&text, &sa2);
+        for pat in test_patterns(&text) {
+            let ans = locate_naive(&text, &pat);
+            assert_eq!(search1.locate(&pat), ans);
+            assert_eq!(search2.locate(&pat), ans);
+            assert_eq!(search3.locate(&pat), ans);
+            let (range, len) = search1.longest_prefix_match(&pat);
+            assert_eq!(range, search1.range(&pat[..len]));
+            assert!(!range.is_empty() || text.is_empty());
+            assert!(len == pat.len() || search1.count(&pat[..len + 1]) == 0);
+        }
+    }
+}
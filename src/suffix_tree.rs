@@ -1,2 +1,3 @@
-// pub mod suffix_tree;
+pub mod generalized;
 pub mod ukkonnen_vec_lib;
+pub mod weiner;
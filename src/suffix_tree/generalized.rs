@@ -0,0 +1,239 @@
+//! Generalized suffix tree
+//!
+//! The suffix tree of documents $D_0, \ldots, D_{m-1}$ is the suffix tree of $D_0 \\$_0 D_1 \\$_1 \cdots D_{m-1} \\$_{m-1}$,
+//! where $\\$_d$ are distinct separators that do not occur in the documents.
+
+use std::{collections::HashSet, hash::Hash};
+
+use super::ukkonnen_vec_lib::Tree;
+
+/// Character of the concatenated documents.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DocChar<T> {
+    /// Separator following the document of the ID.
+    Sep(usize),
+    Char(T),
+}
+
+/// Suffix tree of multiple documents.
+pub struct GeneralizedTree<T> {
+    tree: Tree<DocChar<T>>,
+    /// `starts[d]` is the beginning position of document `d` in the concatenated text,
+    /// and the last one is the length of the concatenated text.
+    starts: Vec<usize>,
+    /// `doc_counts[v]` is the number of distinct documents that contain the path label of node `v`.
+    doc_counts: Vec<usize>,
+}
+
+impl<T> GeneralizedTree<T>
+where
+    T: Ord + Hash + Clone,
+{
+    /// Builds the generalized suffix tree of documents via the suffix array of their concatenation.
+    pub fn from_docs(docs: &[Vec<T>]) -> Self {
+        let (text, starts) = Self::concat(docs);
+        Self::with_tree(Tree::from_text_sa(&text), starts)
+    }
+
+    /// Builds the generalized suffix tree of documents by Ukkonen's algorithm.
+    pub fn from_docs_online(docs: &[Vec<T>]) -> Self {
+        let (text, starts) = Self::concat(docs);
+        Self::with_tree(Tree::from_text_finished(&text), starts)
+    }
+
+    fn concat(docs: &[Vec<T>]) -> (Vec<DocChar<T>>, Vec<usize>) {
+        let mut text = vec![];
+        let mut starts = vec![];
+        for (d, doc) in docs.iter().enumerate() {
+            starts.push(text.len());
+            text.extend(doc.iter().cloned().map(DocChar::Char));
+            text.push(DocChar::Sep(d));
+        }
+        starts.push(text.len());
+        (text, starts)
+    }
+
+    fn with_tree(tree: Tree<DocChar<T>>, starts: Vec<usize>) -> Self {
+        let mut res = Self {
+            tree,
+            starts,
+            doc_counts: vec![],
+        };
+        res.doc_counts = res.count_docs();
+        res
+    }
+
+    /// Counts distinct documents under every node by merging smaller sets into larger ones.
+    fn count_docs(&self) -> Vec<usize> {
+        let tree = &self.tree;
+        let mut order = vec![];
+        let mut stack = vec![tree.root()];
+        while let Some(nid) = stack.pop() {
+            order.push(nid);
+            stack.extend(tree.children(nid));
+        }
+        let mut sets: Vec<HashSet<usize>> = vec![HashSet::new(); tree.num_nodes()];
+        let mut counts = vec![0; tree.num_nodes()];
+        for &nid in order.iter().rev() {
+            let mut set = HashSet::new();
+            if let Some(doc) = self.leaf_doc(nid) {
+                set.insert(doc);
+            }
+            for cid in tree.children(nid) {
+                let mut child = std::mem::take(&mut sets[cid]);
+                if child.len() > set.len() {
+                    std::mem::swap(&mut child, &mut set);
+                }
+                set.extend(child);
+            }
+            counts[nid] = set.len();
+            sets[nid] = set;
+        }
+        counts
+    }
+
+    /// Returns the number of documents.
+    pub fn num_docs(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    /// Returns the underlying suffix tree of the concatenated documents.
+    pub fn tree(&self) -> &Tree<DocChar<T>> {
+        &self.tree
+    }
+
+    /// Returns the document containing position `pos` of the concatenated text,
+    /// where a separator belongs to the preceding document.
+    pub fn doc_id(&self, pos: usize) -> usize {
+        self.starts.partition_point(|&s| s <= pos) - 1
+    }
+
+    /// Returns the document of the suffix represented by leaf `nid`.
+    pub fn leaf_doc(&self, nid: usize) -> Option<usize> {
+        self.tree
+            .leaf_pos(nid)
+            .filter(|&pos| pos < self.tree.text().len())
+            .map(|pos| self.doc_id(pos))
+    }
+
+    /// Returns the number of distinct documents that contain the path label of node `nid`.
+    pub fn doc_count(&self, nid: usize) -> usize {
+        self.doc_counts[nid]
+    }
+
+    fn find(&self, pattern: &[T]) -> Option<usize> {
+        let pattern: Vec<DocChar<T>> = pattern.iter().cloned().map(DocChar::Char).collect();
+        self.tree.find(&pattern)
+    }
+
+    /// Returns the number of documents that contain a given pattern.
+    pub fn count_documents(&self, pattern: &[T]) -> usize {
+        self.find(pattern).map_or(0, |nid| self.doc_count(nid))
+    }
+
+    /// Returns the IDs of documents that contain a given pattern in ascending order.
+    pub fn documents(&self, pattern: &[T]) -> Vec<usize> {
+        let mut res: Vec<usize> = match self.find(pattern) {
+            Some(nid) => self
+                .tree
+                .leaf_positions(nid)
+                .into_iter()
+                .map(|pos| self.doc_id(pos))
+                .collect(),
+            None => vec![],
+        };
+        res.sort_unstable();
+        res.dedup();
+        res
+    }
+
+    /// Returns a longest string that occurs in at least `k` documents.
+    pub fn longest_common_substring(&self, k: usize) -> Vec<T> {
+        assert!(
+            0 < k && k <= self.num_docs(),
+            "k must be in [1, the number of documents]"
+        );
+        if k == 1 {
+            // The longest document itself.
+            let d = (0..self.num_docs())
+                .max_by_key(|&d| self.starts[d + 1] - self.starts[d])
+                .unwrap();
+            return self.chars(&self.tree.text()[self.starts[d]..self.starts[d + 1] - 1]);
+        }
+        // The path labels of internal nodes do not contain separators since they occur at least twice.
+        let best = (0..self.tree.num_nodes())
+            .filter(|&nid| !self.tree.is_leaf(nid) && self.doc_count(nid) >= k)
+            .max_by_key(|&nid| self.tree.depth(nid))
+            .unwrap_or(self.tree.root());
+        self.chars(self.tree.label(best))
+    }
+
+    fn chars(&self, s: &[DocChar<T>]) -> Vec<T> {
+        s.iter()
+            .map(|c| match c {
+                DocChar::Char(c) => c.clone(),
+                DocChar::Sep(_) => unreachable!(),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_generalized_tree() {
+    let docs = vec![
+        br"abracadabra".to_vec(),
+        br"cadabra".to_vec(),
+        br"bracket".to_vec(),
+    ];
+    for gst in [
+        GeneralizedTree::from_docs(&docs),
+        GeneralizedTree::from_docs_online(&docs),
+    ] {
+        assert_eq!(gst.num_docs(), 3);
+        assert_eq!(gst.documents(br"bra"), vec![0, 1, 2]);
+        assert_eq!(gst.documents(br"cad"), vec![0, 1]);
+        assert_eq!(gst.documents(br"ket"), vec![2]);
+        assert!(gst.documents(br"x").is_empty());
+        assert_eq!(gst.count_documents(br"a"), 3);
+        assert_eq!(gst.count_documents(br"abra"), 2);
+        assert_eq!(gst.longest_common_substring(1), br"abracadabra".to_vec());
+        assert_eq!(gst.longest_common_substring(2), br"cadabra".to_vec());
+        assert_eq!(gst.longest_common_substring(3), br"bra".to_vec());
+    }
+}
+
+#[test]
+fn test_generalized_tree_naive() {
+    use crate::commons::{locate_naive, substrs, test_patterns, test_texts};
+
+    // Collections of similar documents, as the texts are sorted.
+    let texts = test_texts();
+    let mut docs_list: Vec<Vec<Vec<u8>>> = texts.chunks(3).map(|x| x.to_vec()).collect();
+    docs_list.push(vec![br"aaaa".to_vec(), br"aa".to_vec(), br"aaa".to_vec()]);
+    let contains = |doc: &[u8], pat: &[u8]| pat.is_empty() || !locate_naive(doc, pat).is_empty();
+    for docs in docs_list {
+        let gst = GeneralizedTree::from_docs(&docs);
+        let mut pats: Vec<Vec<u8>> = docs.iter().flat_map(|doc| test_patterns(doc)).collect();
+        pats.sort();
+        pats.dedup();
+        for pat in pats.iter().filter(|x| !x.is_empty()) {
+            let ans: Vec<usize> = (0..docs.len())
+                .filter(|&d| contains(&docs[d], pat))
+                .collect();
+            assert_eq!(gst.documents(pat), ans);
+            assert_eq!(gst.count_documents(pat), ans.len());
+        }
+        for k in 2..=docs.len() {
+            let len = docs
+                .iter()
+                .flat_map(|doc| substrs(doc))
+                .filter(|x| docs.iter().filter(|doc| contains(doc, x)).count() >= k)
+                .map(|x| x.len())
+                .max()
+                .unwrap_or(0);
+            let lcs = gst.longest_common_substring(k);
+            assert_eq!(lcs.len(), len);
+            assert!(docs.iter().filter(|doc| contains(doc, &lcs)).count() >= k);
+        }
+    }
+}
@@ -1,9 +1,18 @@
 use std::{
+    cell::OnceCell,
     collections::{HashMap, HashSet},
     fs::File,
+    hash::Hash,
     io::Write,
+    ops::Range,
 };
 
+use crate::commons::count_occ;
+use crate::lcp::make_lcpa_kasai;
+use crate::stree_json::Label;
+use crate::suffix_array::misc::SaIndex;
+use crate::suffix_array::sais::make_sa_induce_ord;
+
 #[derive(Debug)]
 pub struct Graph {
     pub nodes: HashSet<usize>,
@@ -16,25 +25,31 @@ enum NodeType {
     Internal,
     Leaf,
 }
-struct Node {
+struct Node<T> {
     parent: Option<usize>,
     ebeg: usize,
+    /// Length of the edge label, where `0` means that the edge of a leaf extends to the end of the text.
     elen: usize,
-    children: HashMap<u8, usize>,
+    /// String depth of the root and internal nodes, which is not used for leaves.
+    depth: usize,
+    /// Children sorted by the first characters of their edges,
+    /// where `None` is the key of the terminator.
+    children: Vec<(Option<T>, usize)>,
 }
 
-impl Node {
-    fn new(parent: Option<usize>, ebeg: usize, elen: usize) -> Self {
+impl<T> Node<T> {
+    fn new(parent: Option<usize>, ebeg: usize, elen: usize, depth: usize) -> Self {
         Self {
             parent,
             ebeg,
             elen,
-            children: HashMap::new(),
+            depth,
+            children: vec![],
         }
     }
 
     fn node_type(&self) -> NodeType {
-        if self.parent == None {
+        if self.parent.is_none() {
             NodeType::Root
         } else if self.elen > 0 {
             NodeType::Internal
@@ -44,6 +59,27 @@ impl Node {
     }
 }
 
+impl<T> Node<T>
+where
+    T: Ord,
+{
+    /// Returns the child whose edge begins with `c`.
+    fn child(&self, c: Option<&T>) -> Option<usize> {
+        self.children
+            .binary_search_by(|(k, _)| k.as_ref().cmp(&c))
+            .ok()
+            .map(|i| self.children[i].1)
+    }
+
+    /// Sets the child whose edge begins with `c`, replacing the existing one.
+    fn set_child(&mut self, c: Option<T>, cid: usize) {
+        match self.children.binary_search_by(|(k, _)| k.cmp(&c)) {
+            Ok(i) => self.children[i].1 = cid,
+            Err(i) => self.children.insert(i, (c, cid)),
+        }
+    }
+}
+
 struct ImplicitNode {
     nid: usize,
     match_len: usize,
@@ -62,38 +98,152 @@ impl ImplicitNode {
     }
 }
 
-pub struct Tree {
-    text: Vec<u8>,
+/// Suffix tree built online by Ukkonen's algorithm.
+///
+/// Nodes are identified by `usize` IDs, where `0` is the root.
+/// Until the tree is finished by [`Tree::finish`], suffixes that occur elsewhere in the text are implicit,
+/// i.e., they end in the middle of edges or at internal nodes without leaves.
+pub struct Tree<T> {
+    text: Vec<T>,
     ap: ImplicitNode,
-    nodes: Vec<Node>,
+    nodes: Vec<Node<T>>,
     slink: HashMap<usize, usize>,
+    finished: bool,
+    /// Cache of [`Tree::leaf_counts`], which is cleared when the tree changes.
+    leaf_counts: OnceCell<Vec<usize>>,
 }
 
-impl Tree {
+impl<T> Tree<T>
+where
+    T: Ord + Hash + Clone,
+{
     pub fn new() -> Self {
         Self {
             text: vec![],
             ap: ImplicitNode::root(),
-            nodes: vec![Node::new(None, 0, 0)],
+            nodes: vec![Node::new(None, 0, 0, 0)],
             slink: HashMap::new(),
+            finished: false,
+            leaf_counts: OnceCell::new(),
+        }
+    }
+
+    /// Builds the suffix tree of a given text.
+    pub fn from_text(text: &[T]) -> Self {
+        let mut tree = Self::new();
+        text.iter().for_each(|c| tree.add_suffix(c.clone()));
+        tree
+    }
+
+    /// Builds the finished suffix tree of a given text.
+    pub fn from_text_finished(text: &[T]) -> Self {
+        let mut tree = Self::from_text(text);
+        tree.finish();
+        tree
+    }
+
+    /// Builds the finished suffix tree of a given text from its suffix array and LCP array.
+    ///
+    /// Nodes are created bottom-up along the rightmost path kept in a stack,
+    /// inserting suffixes in lexicographic order in $O(n)$ time.
+    ///
+    /// Suffix links are computed afterwards in $O(n)$ time as well.
+    /// If an internal node of depth $d$ is an ancestor of the leaf of suffix $p$,
+    /// its suffix link points to the ancestor of depth $d-1$ of the leaf of suffix $p+1$.
+    /// Such ancestors are found by a depth-first traversal keeping the nodes on the current path by their depths.
+    pub fn from_sa_lcp<I>(text: &[T], sa: &[I], lcp: &[I]) -> Self
+    where
+        I: SaIndex,
+    {
+        debug_assert_eq!(text.len(), sa.len());
+        debug_assert_eq!(text.len(), lcp.len());
+        let n = text.len();
+        let mut tree = Self::new();
+        tree.text = text.to_vec();
+        tree.finished = true;
+
+        // The leaf of the empty suffix is the smallest one.
+        let leaf = tree.create_node(n, 0, 0);
+        tree.set_par_child(tree.root(), leaf);
+        let mut stack = vec![tree.root()];
+        // Pairs of an internal node and a suffix whose leaf is under the node.
+        let mut below = vec![];
+        for (i, p) in sa.iter().map(|p| p.into_usize()).enumerate() {
+            let l = if i == 0 { 0 } else { lcp[i].into_usize() };
+            // A leaf of depth `l` is the suffix `text[sa[i-1]..]`, which becomes the terminator leaf by the split.
+            let mut last = None;
+            while tree.depth(*stack.last().unwrap()) > l || tree.is_leaf(*stack.last().unwrap()) {
+                last = stack.pop();
+            }
+            let top = *stack.last().unwrap();
+            let pid = if tree.depth(top) < l {
+                // Splits the edge to `last` at depth `l`.
+                let cid = last.unwrap();
+                let bid = tree.split_at(cid, l - tree.depth(top));
+                below.push((bid, p));
+                stack.push(bid);
+                bid
+            } else {
+                top
+            };
+            let leaf = tree.create_node(p + l, 0, 0);
+            tree.set_par_child(pid, leaf);
+            stack.push(leaf);
+        }
+
+        // `heads[q]` and `next` list the internal nodes above the leaf of suffix `q-1`.
+        let mut heads = vec![usize::MAX; n + 1];
+        let mut next = vec![usize::MAX; below.len()];
+        for (k, &(_, p)) in below.iter().enumerate() {
+            next[k] = heads[p + 1];
+            heads[p + 1] = k;
+        }
+        // `path[d]` is the node of depth `d` on the path from the root to the current node.
+        let mut path = vec![usize::MAX; n + 1];
+        let mut stack = vec![tree.root()];
+        while let Some(nid) = stack.pop() {
+            match tree.leaf_pos(nid) {
+                Some(q) => {
+                    let mut k = heads[q];
+                    while k != usize::MAX {
+                        let vid = below[k].0;
+                        tree.slink.insert(vid, path[tree.depth(vid) - 1]);
+                        k = next[k];
+                    }
+                }
+                None => {
+                    path[tree.depth(nid)] = nid;
+                    stack.extend(tree.children(nid));
+                }
+            }
         }
+        tree
     }
 
-    fn create_node(&mut self, ebeg: usize, elen: usize) -> usize {
-        self.nodes.push(Node::new(None, ebeg, elen));
+    /// Builds the finished suffix tree of a given text via its suffix array and LCP array.
+    pub fn from_text_sa(text: &[T]) -> Self {
+        let sa: Vec<usize> = make_sa_induce_ord(text);
+        let lcp = make_lcpa_kasai(text, &sa);
+        Self::from_sa_lcp(text, &sa, &lcp)
+    }
+
+    fn create_node(&mut self, ebeg: usize, elen: usize, depth: usize) -> usize {
+        self.nodes.push(Node::new(None, ebeg, elen, depth));
         self.nodes.len() - 1
     }
 
     fn set_par_child(&mut self, pid: usize, cid: usize) {
-        let k = self.text[self.nodes[cid].ebeg];
+        let k = self.text.get(self.nodes[cid].ebeg).cloned();
         self.nodes[cid].parent = Some(pid);
-        self.nodes[pid].children.insert(k, cid);
+        self.nodes[pid].set_child(k, cid);
+        self.leaf_counts.take();
     }
 
     fn split_at(&mut self, cid: usize, match_len: usize) -> usize {
         assert_ne!(self.nodes[cid].node_type(), NodeType::Root);
         let pid = self.nodes[cid].parent.unwrap();
-        let bid = self.create_node(self.nodes[cid].ebeg, match_len);
+        let depth = self.nodes[pid].depth + match_len;
+        let bid = self.create_node(self.nodes[cid].ebeg, match_len, depth);
         self.nodes[cid].ebeg += match_len;
         if self.nodes[cid].node_type() == NodeType::Internal {
             self.nodes[cid].elen -= match_len;
@@ -104,17 +254,14 @@ impl Tree {
     }
 
     // move from `ap` with `c`.
-    fn move_ap(&self, ap: &ImplicitNode, c: u8) -> Option<ImplicitNode> {
+    fn move_ap(&self, ap: &ImplicitNode, c: &T) -> Option<ImplicitNode> {
         if ap.at_node() {
-            self.nodes[ap.nid]
-                .children
-                .get(&c)
-                .map(|&cid| ImplicitNode {
-                    nid: cid,
-                    match_len: 1,
-                    edge_len: self.nodes[cid].elen,
-                })
-        } else if self.text[self.nodes[ap.nid].ebeg + ap.match_len] == c {
+            self.nodes[ap.nid].child(Some(c)).map(|cid| ImplicitNode {
+                nid: cid,
+                match_len: 1,
+                edge_len: self.nodes[cid].elen,
+            })
+        } else if &self.text[self.nodes[ap.nid].ebeg + ap.match_len] == c {
             Some(ImplicitNode {
                 nid: ap.nid,
                 match_len: ap.match_len + 1,
@@ -127,17 +274,17 @@ impl Tree {
 
     // move from node `nid` with key `key`.
     // it is guaranteed that we can move with key `key`.
-    fn move_trust(&self, nid: usize, key: &[u8]) -> ImplicitNode {
+    fn move_trust(&self, nid: usize, key: &[T]) -> ImplicitNode {
         if key.is_empty() {
             assert_ne!(self.nodes[nid].node_type(), NodeType::Leaf);
             ImplicitNode {
                 nid,
-                match_len: 0,
+                match_len: self.nodes[nid].elen,
                 edge_len: self.nodes[nid].elen,
             }
         } else {
-            match self.nodes[nid].children.get(&key[0]) {
-                Some(&cid) => {
+            match self.nodes[nid].child(Some(&key[0])) {
+                Some(cid) => {
                     if self.elen(cid) <= key.len() {
                         self.move_trust(cid, &key[self.nodes[cid].elen..])
                     } else {
@@ -148,20 +295,41 @@ impl Tree {
                         }
                     }
                 }
-                None => panic!(
-                    "move_trust must move from node[{:?}] with key[{:?}]",
-                    nid, key
-                ),
+                None => panic!("move_trust must move from node[{:?}]", nid),
             }
         }
     }
 
-    pub fn add_suffix(&mut self, c: u8) {
-        println!("add {}", std::str::from_utf8(&[c]).unwrap());
+    pub fn add_suffix(&mut self, c: T) {
+        assert!(!self.finished, "the tree is already finished");
         self.text.push(c);
+        self.extend(self.text.len() - 1);
+    }
+
+    /// Appends a unique terminator to the text conceptually, which makes all the suffixes explicit.
+    ///
+    /// Then, every suffix is represented by a leaf, including the empty suffix as a child of the root.
+    /// The terminator is not a part of the text, and no character can be added after it.
+    pub fn finish(&mut self) {
+        if !self.finished {
+            self.extend(self.text.len());
+            self.finished = true;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Inserts the suffixes ending at `pos`, where `pos = text.len()` means the terminator.
+    fn extend(&mut self, pos: usize) {
         let mut prev_node: Option<usize> = None;
         loop {
-            if let Some(ap_next) = self.move_ap(&self.ap, c) {
+            let ap_next = match self.text.get(pos) {
+                Some(c) => self.move_ap(&self.ap, c),
+                None => None,
+            };
+            if let Some(ap_next) = ap_next {
                 if self.ap.at_node() {
                     if let Some(prev_id) = prev_node {
                         self.slink.insert(prev_id, self.ap.nid);
@@ -176,9 +344,8 @@ impl Tree {
                 } else {
                     self.split_at(self.ap.nid, self.ap.match_len)
                 };
-                let cid = self.create_node(self.text.len() - 1, 0);
+                let cid = self.create_node(pos, 0, 0);
                 self.set_par_child(bid, cid);
-                println!("insert {} to {}", bid, cid);
 
                 // update suffix link
                 if let Some(prev_id) = prev_node {
@@ -203,10 +370,6 @@ impl Tree {
                 }
             }
         }
-
-        println!("graph: {:?}", self.graph());
-        // println!("graph: {:?}", self.serialize());
-        println!();
     }
 
     // return edge lentgth of node `nid`.
@@ -221,19 +384,216 @@ impl Tree {
         }
     }
 
-    fn estr(&self, nid: usize) -> String {
+    /// Returns the text.
+    pub fn text(&self) -> &[T] {
+        &self.text
+    }
+
+    /// Returns the ID of the root.
+    pub const fn root(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_leaf(&self, nid: usize) -> bool {
+        self.nodes[nid].node_type() == NodeType::Leaf
+    }
+
+    pub fn parent(&self, nid: usize) -> Option<usize> {
+        self.nodes[nid].parent
+    }
+
+    /// Returns the children of node `nid` in the lexicographic order of their edges.
+    pub fn children(
+        &self,
+        nid: usize,
+    ) -> impl DoubleEndedIterator<Item = usize> + ExactSizeIterator + '_ {
+        self.nodes[nid].children.iter().map(|&(_, cid)| cid)
+    }
+
+    /// Returns the child of node `nid` whose edge begins with `c`.
+    pub fn child(&self, nid: usize, c: &T) -> Option<usize> {
+        self.nodes[nid].child(Some(c))
+    }
+
+    /// Returns the node that the suffix link of node `nid` points to.
+    ///
+    /// The suffix link of an internal node labeled $cw$ for a character $c$ points to the node labeled $w$.
+    pub fn suffix_link(&self, nid: usize) -> Option<usize> {
+        self.slink.get(&nid).copied()
+    }
+
+    /// Returns the beginning position of the suffix represented by leaf `nid`.
+    pub fn leaf_pos(&self, nid: usize) -> Option<usize> {
+        let node = &self.nodes[nid];
+        match node.node_type() {
+            NodeType::Leaf => Some(node.ebeg - self.nodes[node.parent.unwrap()].depth),
+            _ => None,
+        }
+    }
+
+    /// Returns the string depth of node `nid`, i.e., the length of its path label.
+    pub fn depth(&self, nid: usize) -> usize {
+        match self.leaf_pos(nid) {
+            Some(pos) => self.text.len() - pos,
+            None => self.nodes[nid].depth,
+        }
+    }
+
+    /// Returns the range of the text where the label of the edge to node `nid` occurs.
+    pub fn edge_range(&self, nid: usize) -> Range<usize> {
         let ebeg = self.nodes[nid].ebeg;
-        let eend = ebeg + self.elen(nid);
-        String::from_utf8(self.text[ebeg..eend].to_vec()).unwrap()
+        ebeg..ebeg + self.elen(nid)
     }
 
-    fn estr_all(&self, nid: usize) -> String {
-        match self.nodes[nid].parent {
-            Some(pid) => self.estr_all(pid) + &self.estr(nid),
-            None => self.estr(nid),
+    /// Returns the label of the edge to node `nid`.
+    pub fn edge_label(&self, nid: usize) -> &[T] {
+        &self.text[self.edge_range(nid)]
+    }
+
+    /// Returns a range of the text where the path label of node `nid` occurs.
+    pub fn label_range(&self, nid: usize) -> Range<usize> {
+        let node = &self.nodes[nid];
+        match node.parent {
+            Some(pid) => node.ebeg - self.nodes[pid].depth..node.ebeg + self.elen(nid),
+            None => 0..0,
         }
     }
 
+    /// Returns the path label of node `nid`, i.e., the string spelled from the root to the node.
+    pub fn label(&self, nid: usize) -> &[T] {
+        &self.text[self.label_range(nid)]
+    }
+
+    /// Returns the numbers of the leaves under every node, except for the leaf of the empty suffix.
+    ///
+    /// For a finished tree, they are the numbers of occurrences of the path labels.
+    /// They are computed once in $O(n)$ time until the tree changes.
+    pub fn leaf_counts(&self) -> &[usize] {
+        self.leaf_counts.get_or_init(|| {
+            let mut order = vec![];
+            let mut stack = vec![self.root()];
+            while let Some(nid) = stack.pop() {
+                order.push(nid);
+                stack.extend(self.children(nid));
+            }
+            let mut counts = vec![0; self.nodes.len()];
+            for &nid in order.iter().rev() {
+                counts[nid] = match self.leaf_pos(nid) {
+                    Some(pos) => usize::from(pos < self.text.len()),
+                    None => self.children(nid).map(|cid| counts[cid]).sum(),
+                };
+            }
+            counts
+        })
+    }
+
+    /// Returns the beginning positions of the suffixes represented by the leaves under node `nid`
+    /// in the lexicographic order of the suffixes.
+    /// The empty suffix of a finished tree is not reported.
+    pub fn leaf_positions(&self, nid: usize) -> Vec<usize> {
+        let mut res = vec![];
+        let mut stack = vec![nid];
+        while let Some(nid) = stack.pop() {
+            match self.leaf_pos(nid) {
+                Some(pos) if pos < self.text.len() => res.push(pos),
+                Some(_) => {}
+                None => stack.extend(self.children(nid).rev()),
+            }
+        }
+        res
+    }
+
+    /// Returns the suffix array of the text.
+    pub fn suffix_array<I>(&self) -> Vec<I>
+    where
+        I: SaIndex,
+    {
+        assert!(self.finished, "the tree must be finished");
+        self.leaf_positions(self.root())
+            .into_iter()
+            .map(I::from_usize)
+            .collect()
+    }
+
+    /// Returns the highest node whose path label begins with a given pattern.
+    pub fn find(&self, pattern: &[T]) -> Option<usize> {
+        let mut nid = self.root();
+        let mut i = 0;
+        while i < pattern.len() {
+            nid = self.child(nid, &pattern[i])?;
+            let label = self.edge_label(nid);
+            let len = label.len().min(pattern.len() - i);
+            if label[..len] != pattern[i..i + len] {
+                return None;
+            }
+            i += len;
+        }
+        Some(nid)
+    }
+
+    /// Returns the beginning positions of the implicit suffixes, which are not represented by leaves.
+    fn implicit_suffixes(&self) -> Range<usize> {
+        let ap_depth = match self.nodes[self.ap.nid].parent {
+            Some(pid) => self.nodes[pid].depth + self.ap.match_len,
+            None => 0,
+        };
+        self.text.len() - ap_depth..self.text.len()
+    }
+
+    /// Returns the number of occurrences of a given pattern.
+    ///
+    /// It takes $O(m)$ time besides [`Tree::leaf_counts`] for a finished tree,
+    /// and $O(m+d)$ time for an unfinished tree, where $d$ is the number of implicit suffixes.
+    pub fn count_occurrences(&self, pattern: &[T]) -> usize {
+        match self.find(pattern) {
+            Some(nid) => {
+                self.leaf_counts()[nid] + count_occ(&self.text[self.implicit_suffixes()], pattern)
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns the beginning positions of occurrences of a given pattern in ascending order.
+    pub fn locate(&self, pattern: &[T]) -> Vec<usize> {
+        let mut res = match self.find(pattern) {
+            Some(nid) => self.leaf_positions(nid),
+            None => return vec![],
+        };
+        res.extend(
+            self.implicit_suffixes()
+                .filter(|&i| self.text[i..].starts_with(pattern)),
+        );
+        res.sort_unstable();
+        res
+    }
+}
+
+impl<T> Tree<T>
+where
+    T: Ord + Hash + Clone + Label,
+{
+    /// Returns the terminator `$` if node `nid` is a leaf of a finished tree.
+    fn terminator(&self, nid: usize) -> &'static str {
+        if self.finished && self.is_leaf(nid) {
+            "$"
+        } else {
+            ""
+        }
+    }
+
+    fn estr(&self, nid: usize) -> String {
+        T::label(self.edge_label(nid)) + self.terminator(nid)
+    }
+
+    fn estr_all(&self, nid: usize) -> String {
+        T::label(self.label(nid)) + self.terminator(nid)
+    }
+
     pub fn graph(&self) -> Graph {
         let mut nodes = HashSet::new();
         let mut edges = HashSet::new();
@@ -267,8 +627,124 @@ impl Tree {
     }
 }
 
-impl Default for Tree {
+impl<T> Default for Tree<T>
+where
+    T: Ord + Hash + Clone,
+{
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[test]
+fn test_tree() {
+    let tree = Tree::from_text(br"abcabxabcd");
+    let nid = tree.find(br"abc").unwrap();
+    assert_eq!(tree.label(nid), br"abc");
+    assert_eq!(tree.edge_label(nid), br"c");
+    assert_eq!(tree.depth(nid), 3);
+    assert_eq!(tree.leaf_positions(nid), vec![0, 6]);
+    assert_eq!(tree.count_occurrences(br"ab"), 3);
+    assert_eq!(tree.count_occurrences(br"bx"), 1);
+    assert_eq!(tree.count_occurrences(br"abd"), 0);
+    assert!(tree.find(br"abd").is_none());
+    let pid = tree.parent(nid).unwrap();
+    assert_eq!(tree.label(pid), br"ab");
+    assert!(tree.children(pid).any(|cid| cid == nid));
+    assert_eq!(tree.label(tree.suffix_link(nid).unwrap()), br"bc");
+
+    // Non-UTF-8 bytes are escaped in labels.
+    let tree = Tree::from_text(&[0xffu8, 0xfe, 0xff]);
+    assert!(tree.serialize().contains("\\\\xff\\\\xfe"));
+}
+
+#[test]
+fn test_tree_naive() {
+    use crate::commons::{locate_naive, test_patterns, test_texts};
+
+    for text in test_texts() {
+        let tree = Tree::from_text(&text);
+        for pat in test_patterns(&text) {
+            let ans = locate_naive(&text, &pat);
+            assert_eq!(tree.count_occurrences(&pat), ans.len());
+        }
+        for nid in 1..tree.num_nodes() {
+            assert_eq!(tree.label(nid).len(), tree.depth(nid));
+            assert_eq!(tree.find(tree.label(nid)), Some(nid));
+            if let Some(link) = tree.suffix_link(nid) {
+                assert_eq!(tree.label(link), &tree.label(nid)[1..]);
+            }
+            if !tree.is_leaf(nid) {
+                assert!(tree.suffix_link(nid).is_some());
+            }
+        }
+    }
+
+    let text: Vec<u32> = vec![70000, 1, 70000, 1, 70000, 2];
+    let tree = Tree::from_text(&text);
+    assert_eq!(tree.count_occurrences(&[70000, 1]), 2);
+    assert_eq!(tree.count_occurrences(&[1, 70000]), 2);
+    assert!(tree.serialize().contains("70000,1,70000"));
+}
+
+#[test]
+fn test_tree_finish() {
+    use crate::commons::{locate_naive, test_patterns, test_texts};
+    use crate::suffix_array::sanaive::make_sa_naive;
+
+    let mut tree = Tree::from_text(br"abab");
+    // The suffixes `ab` and `b` are implicit.
+    assert_eq!(tree.leaf_positions(tree.root()), vec![0, 1]);
+    assert_eq!(tree.locate(br"ab"), vec![0, 2]);
+    tree.finish();
+    assert_eq!(tree.leaf_positions(tree.root()), vec![2, 0, 3, 1]);
+    assert_eq!(tree.suffix_array::<u32>(), vec![2, 0, 3, 1]);
+    let nid = tree.find(br"ab").unwrap();
+    assert_eq!(tree.depth(nid), 2);
+    assert_eq!(tree.leaf_positions(nid), vec![2, 0]);
+    assert!(tree.serialize().contains("\"ab$\""));
+
+    for text in test_texts() {
+        let tree = Tree::from_text_finished(&text);
+        let num_leaves = (0..tree.num_nodes()).filter(|&i| tree.is_leaf(i)).count();
+        assert_eq!(num_leaves, text.len() + 1);
+        for nid in (1..tree.num_nodes()).filter(|&i| !tree.is_leaf(i)) {
+            let link = tree.suffix_link(nid).unwrap();
+            assert_eq!(tree.label(link), &tree.label(nid)[1..]);
+        }
+        let sa: Vec<usize> = tree.suffix_array();
+        assert_eq!(sa, make_sa_naive::<_, usize>(&text));
+        for pat in test_patterns(&text) {
+            let ans = locate_naive(&text, &pat);
+            assert_eq!(tree.count_occurrences(&pat), ans.len());
+            assert_eq!(tree.locate(&pat), ans);
+        }
+    }
+}
+
+#[test]
+fn test_tree_from_sa_lcp() {
+    use crate::commons::enum_strs_len_leq;
+    use crate::fib::fibstr_ab;
+
+    // A long run makes a deep tree.
+    let mut texts = vec![br"mississippi".to_vec(), fibstr_ab(10), vec![b'a'; 2000]];
+    texts.extend(enum_strs_len_leq(br"abc", 6));
+    for text in texts {
+        let tree1 = Tree::from_text_finished(&text);
+        let tree2 = Tree::from_text_sa(&text);
+        assert_eq!(tree1.num_nodes(), tree2.num_nodes());
+        assert_eq!(tree1.suffix_array::<u32>(), tree2.suffix_array::<u32>());
+        for nid in 0..tree2.num_nodes() {
+            let label = tree2.label(nid);
+            if !tree2.edge_label(nid).is_empty() {
+                assert_eq!(tree2.find(label), Some(nid));
+            }
+            assert_eq!(tree1.locate(label), tree2.locate(label));
+            if !tree2.is_leaf(nid) && nid != tree2.root() {
+                let link = tree2.suffix_link(nid).unwrap();
+                assert_eq!(tree2.label(link), &label[1..]);
+            }
+        }
+    }
+}
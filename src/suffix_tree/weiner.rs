@@ -1,188 +1,351 @@
-// use std::collections::HashMap;
-
-// #[derive(Debug, PartialEq, Eq)]
-// pub enum BinaryTree<T> {
-//     Nil,
-//     Node {
-//         val: T,
-//         left: Box<BinaryTree<T>>,
-//         right: Box<BinaryTree<T>>,
-//     },
-// }
-
-// impl<T> BinaryTree<T> {
-//     pub fn replace(&mut self, to: Self) {
-//         *self = to;
-//     }
-// }
-
-// #[macro_export]
-// macro_rules! bin_tree {
-//     (val: $val:expr, left: $left:expr, right: $right:expr $(,)?) => {
-//         BinaryTree::Node {
-//             val: $val,
-//             left: Box::new($left),
-//             right: Box::new($right),
-//         }
-//     };
-//     (val: $val:expr,right: $right:expr $(,)?) => {
-//         bin_tree! {
-//             val:$val,
-//             left:bin_tree!(),
-//             right: $right,
-//         }
-//     };
-//     (val:$val:expr, left: $left:expr $(,)?) => {
-//         bin_tree! {
-//             val:$val,
-//             left:$left,
-//             right : bin_tree!()
-//         }
-//     };
-//     (val:$val:expr $(,)?) => {
-//         bin_tree!(val: $val, left: bin_tree!(), right: bin_tree!(),)
-//     };
-//     () => {
-//         BinaryTree::Nil
-//     };
-// }
-
-// struct Node2 {
-//     in_edge: Vec<u8>,
-//     children: HashMap<u8, usize>,
-// }
-// impl Node2 {
-//     fn new(in_edge: Vec<u8>) -> Node2 {
-//         Node2 {
-//             in_edge: in_edge,
-//             children: HashMap::new(),
-//         }
-//     }
-//     fn copy(self: &Self) -> Self {
-//         Node2 {
-//             in_edge: self.in_edge.clone(),
-//             children: HashMap::new(),
-//         }
-//     }
-// }
-// struct Trie2 {
-//     // root: Node,
-//     nodes: Vec<Node2>,
-// }
-// impl Trie2 {
-//     fn new() -> Self {
-//         let mut nodes = vec![Node2 {
-//             // in_edge: "hoge".to_string().into_bytes(),
-//             in_edge: "hoge".as_bytes().to_vec(),
-//             children: HashMap::new(),
-//         }];
-//         Trie2 { nodes: nodes }
-//     }
-//     fn create_node(&mut self, in_edge: Vec<u8>) -> &Node2 {
-//         self.nodes.push(Node2::new(in_edge));
-//         &self.nodes[self.nodes.len() - 1]
-//     }
-//     // fn set_parent(&mut self, par: &mut Node, child: &Node) {
-//     //     par.children
-//     //         .insert(child.in_edge[0], Box::new(child.copy()));
-//     // }
-//     fn set_parent(&mut self, par_id: usize, child_id: usize) {
-//         // let child = &self.nodes[child_id];
-//         let key = self.nodes[child_id].in_edge[0];
-//         let par = &mut self.nodes[par_id];
-//         par.children.insert(key, child_id);
-//     }
-//     fn set_parent2(&self, par: &mut Node2, child_id: usize) {
-//         let child = &self.nodes[child_id];
-//         let key = self.nodes[child_id].in_edge[0];
-//         // let par = &mut self.nodes[par_id];
-//         par.children.insert(key, child_id);
-//     }
-//     fn add(&mut self, key: &[u8]) {
-//         let leaf = Node2::new("".as_bytes().to_vec());
-//         self.set_parent(0, self.nodes.len());
-//         // self.nodes[0].children.insert(key[0], Box::new(leaf));
-//     }
-//     // fn estr(&self, node: &Node) -> String {
-//     //     let s = "hoge";
-//     //     String::from_utf8(node.in_edge.clone()).unwrap()
-//     // }
-//     // fn estr(&self, node: &Node) -> &Vec<u8> {
-//     //     &node.in_edge
-//     // }
-//     fn estr(&self, nid: usize) -> &Vec<u8> {
-//         &self.nodes[nid].in_edge
-//     }
-// }
-// struct Node {
-//     in_edge: Vec<u8>,
-//     children: HashMap<u8, Box<Node>>,
-// }
-// impl Node {
-//     fn new(in_edge: Vec<u8>) -> Node {
-//         Node {
-//             in_edge: in_edge,
-//             children: HashMap::new(),
-//         }
-//     }
-//     fn copy(self: &Self) -> Self {
-//         Node {
-//             in_edge: self.in_edge.clone(),
-//             children: HashMap::new(),
-//         }
-//     }
-// }
-// struct Trie {
-//     // root: Node,
-//     nodes: Vec<Node>,
-// }
-// impl Trie {
-//     fn new() -> Trie {
-//         let mut nodes = vec![Node {
-//             // in_edge: "hoge".to_string().into_bytes(),
-//             in_edge: "hoge".as_bytes().to_vec(),
-//             children: HashMap::new(),
-//         }];
-//         Trie { nodes: nodes }
-//     }
-//     fn create_node(&mut self, in_edge: Vec<u8>) -> &Node {
-//         self.nodes.push(Node::new(in_edge));
-//         &self.nodes[self.nodes.len() - 1]
-//     }
-//     // fn set_parent(&mut self, par: &mut Node, child: &Node) {
-//     //     par.children
-//     //         .insert(child.in_edge[0], Box::new(child.copy()));
-//     // }
-//     fn set_parent(&mut self, par: &mut Node, child: Box<Node>) {
-//         par.children.insert(child.in_edge[0], child);
-//     }
-//     fn add(&mut self, key: &[u8]) {
-//         let leaf = Node::new("".as_bytes().to_vec());
-//         self.nodes[0].children.insert(key[0], Box::new(leaf));
-//     }
-//     // fn estr(&self, node: &Node) -> String {
-//     //     let s = "hoge";
-//     //     String::from_utf8(node.in_edge.clone()).unwrap()
-//     // }
-//     // fn estr(&self, node: &Node) -> &Vec<u8> {
-//     //     &node.in_edge
-//     // }
-//     fn estr2<'a>(&self, node: &'a Node) -> &'a Vec<u8> {
-//         &node.in_edge
-//     }
-// }
-// #[derive(Default)]
-// struct SomeOptions {
-//     foo: i32,
-//     bar: f32,
-// }
-
-// fn main() {
-//     println!("Hellow, world!");
-//     // let s = "aiueo".bytes();
-//     let ss = "banana$".as_bytes();
-//     let mut trie = Trie::new();
-//     trie.add(ss);
-//     let options: SomeOptions = Default::default();
-//     println!("{}", options.bar);
-// }
+//! Weiner's algorithm
+//!
+//! It builds the suffix tree of $T\\$$ online from right to left, i.e., prepending characters to $T$.
+//! Each node $v$ keeps an indicator vector, which tells whether $cv$ occurs in the text for a character $c$,
+//! and a link vector, which points to the node $cv$ if it exists.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::stree_json::{Label, StreeSerde};
+
+struct Node<T> {
+    parent: Option<usize>,
+    /// String depth, i.e., the length of the path label without the terminator.
+    depth: usize,
+    /// Length of a suffix beginning with the path label,
+    /// which is stable against prepending characters to the text.
+    suf_len: usize,
+    /// Children keyed by the first characters of their edges,
+    /// where `None` is the key of the terminator.
+    children: HashMap<Option<T>, usize>,
+    /// Characters `c` such that `c` followed by the path label occurs in the text.
+    indicator: HashSet<T>,
+    /// `links[c]` is the node whose path label is `c` followed by the path label of this node.
+    links: HashMap<T, usize>,
+}
+
+impl<T> Node<T> {
+    fn new(parent: Option<usize>, depth: usize, suf_len: usize) -> Self {
+        Self {
+            parent,
+            depth,
+            suf_len,
+            children: HashMap::new(),
+            indicator: HashSet::new(),
+            links: HashMap::new(),
+        }
+    }
+}
+
+/// Suffix tree built by prepending characters.
+///
+/// All the suffixes are represented by leaves since the text is always terminated by a unique terminator.
+pub struct WeinerTree<T> {
+    /// Text in reverse order.
+    rev: Vec<T>,
+    nodes: Vec<Node<T>>,
+    /// Leaf of the whole text.
+    last_leaf: usize,
+}
+
+impl<T> WeinerTree<T>
+where
+    T: Ord + Hash + Clone,
+{
+    /// Creates the suffix tree of the empty text, which has the leaf of the terminator only.
+    pub fn new() -> Self {
+        let mut tree = Self {
+            rev: vec![],
+            nodes: vec![Node::new(None, 0, 0)],
+            last_leaf: 0,
+        };
+        tree.last_leaf = tree.create_leaf(0, 0);
+        tree
+    }
+
+    /// Builds the suffix tree of a given text reading it from right to left.
+    pub fn from_text(text: &[T]) -> Self {
+        let mut tree = Self::new();
+        text.iter().rev().for_each(|c| tree.prepend(c.clone()));
+        tree
+    }
+
+    /// Returns the character at offset `k` of the suffix of length `suf_len`, or `None` for the terminator.
+    fn char_at(&self, suf_len: usize, k: usize) -> Option<&T> {
+        if k < suf_len {
+            Some(&self.rev[suf_len - 1 - k])
+        } else {
+            None
+        }
+    }
+
+    fn set_par_child(&mut self, pid: usize, cid: usize) {
+        let node = &self.nodes[cid];
+        let key = self.char_at(node.suf_len, self.nodes[pid].depth).cloned();
+        self.nodes[cid].parent = Some(pid);
+        self.nodes[pid].children.insert(key, cid);
+    }
+
+    /// Creates the leaf of the suffix of length `suf_len` under node `pid`.
+    fn create_leaf(&mut self, pid: usize, suf_len: usize) -> usize {
+        self.nodes.push(Node::new(None, suf_len, suf_len));
+        let cid = self.nodes.len() - 1;
+        self.set_par_child(pid, cid);
+        cid
+    }
+
+    /// Splits the edge to node `cid` at string depth `depth`.
+    fn split_at(&mut self, cid: usize, depth: usize) -> usize {
+        let pid = self.nodes[cid].parent.unwrap();
+        let mut node = Node::new(None, depth, self.nodes[cid].suf_len);
+        node.indicator = self.nodes[cid].indicator.clone();
+        self.nodes.push(node);
+        let bid = self.nodes.len() - 1;
+        self.set_par_child(pid, bid);
+        self.set_par_child(bid, cid);
+        bid
+    }
+
+    /// Prepends a character to the text.
+    pub fn prepend(&mut self, c: T) {
+        self.rev.push(c.clone());
+        let len = self.rev.len();
+
+        // Finds the lowest ancestor `v` of the last leaf such that `c` followed by its label occurs.
+        let mut path = vec![];
+        let mut v = Some(self.last_leaf);
+        while let Some(x) = v {
+            if self.nodes[x].indicator.contains(&c) {
+                break;
+            }
+            path.push(x);
+            v = self.nodes[x].parent;
+        }
+        // Now `c` followed by the labels of the nodes on the path occurs as a prefix of the new text.
+        // It must be done before splitting an edge, which copies the indicator vector of the lower node.
+        for x in path {
+            self.nodes[x].indicator.insert(c.clone());
+        }
+
+        let pid = match v {
+            // `c` does not occur in the old text.
+            None => self.root(),
+            Some(v) => {
+                // Finds the lowest ancestor `w` of `v` that has a link with `c`,
+                // and the locus of `c` followed by the label of `v` is on the edge below the node linked from `w`.
+                let mut w = v;
+                while !self.nodes[w].links.contains_key(&c) && w != self.root() {
+                    w = self.nodes[w].parent.unwrap();
+                }
+                let top = self.nodes[w].links.get(&c).copied().unwrap_or(self.root());
+                let depth = self.nodes[v].depth + 1;
+                if self.nodes[top].depth == depth {
+                    top
+                } else {
+                    let key = self.char_at(len, self.nodes[top].depth).cloned();
+                    let cid = self.nodes[top].children[&key];
+                    let bid = self.split_at(cid, depth);
+                    self.nodes[v].links.insert(c.clone(), bid);
+                    bid
+                }
+            }
+        };
+        let leaf = self.create_leaf(pid, len);
+        let last_leaf = self.last_leaf;
+        self.nodes[last_leaf].links.insert(c, leaf);
+        self.last_leaf = leaf;
+    }
+
+    /// Returns the length of the text.
+    pub fn len(&self) -> usize {
+        self.rev.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the text.
+    pub fn text(&self) -> Vec<T> {
+        self.rev.iter().rev().cloned().collect()
+    }
+
+    /// Returns the ID of the root.
+    pub const fn root(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_leaf(&self, nid: usize) -> bool {
+        nid != self.root() && self.nodes[nid].children.is_empty()
+    }
+
+    pub fn parent(&self, nid: usize) -> Option<usize> {
+        self.nodes[nid].parent
+    }
+
+    /// Returns the children of node `nid` in the lexicographic order of their edges.
+    pub fn children(&self, nid: usize) -> Vec<usize> {
+        let mut children: Vec<(&Option<T>, usize)> = self.nodes[nid]
+            .children
+            .iter()
+            .map(|(k, &cid)| (k, cid))
+            .collect();
+        children.sort();
+        children.into_iter().map(|(_, cid)| cid).collect()
+    }
+
+    /// Returns the string depth of node `nid`.
+    pub fn depth(&self, nid: usize) -> usize {
+        self.nodes[nid].depth
+    }
+
+    /// Returns the node whose path label is `c` followed by the path label of node `nid`, if exists.
+    pub fn link(&self, nid: usize, c: &T) -> Option<usize> {
+        self.nodes[nid].links.get(c).copied()
+    }
+
+    /// Returns the path label of node `nid`.
+    pub fn label(&self, nid: usize) -> Vec<T> {
+        let node = &self.nodes[nid];
+        (0..node.depth)
+            .map(|k| self.char_at(node.suf_len, k).unwrap().clone())
+            .collect()
+    }
+
+    /// Returns the label of the edge to node `nid`.
+    pub fn edge_label(&self, nid: usize) -> Vec<T> {
+        let node = &self.nodes[nid];
+        let beg = node.parent.map_or(0, |pid| self.nodes[pid].depth);
+        (beg..node.depth)
+            .map(|k| self.char_at(node.suf_len, k).unwrap().clone())
+            .collect()
+    }
+
+    /// Returns the beginning position of the suffix represented by leaf `nid`.
+    pub fn leaf_pos(&self, nid: usize) -> Option<usize> {
+        if self.is_leaf(nid) {
+            Some(self.len() - self.nodes[nid].suf_len)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the highest node whose path label begins with a given pattern.
+    pub fn find(&self, pattern: &[T]) -> Option<usize> {
+        let mut nid = self.root();
+        while self.nodes[nid].depth < pattern.len() {
+            let depth = self.nodes[nid].depth;
+            nid = *self.nodes[nid]
+                .children
+                .get(&Some(pattern[depth].clone()))?;
+            let node = &self.nodes[nid];
+            let end = node.depth.min(pattern.len());
+            if (depth..end).any(|k| self.char_at(node.suf_len, k) != Some(&pattern[k])) {
+                return None;
+            }
+        }
+        Some(nid)
+    }
+
+    /// Returns the beginning positions of occurrences of a given pattern in ascending order.
+    pub fn locate(&self, pattern: &[T]) -> Vec<usize> {
+        let mut res = vec![];
+        let mut stack: Vec<usize> = self.find(pattern).into_iter().collect();
+        while let Some(nid) = stack.pop() {
+            match self.leaf_pos(nid) {
+                Some(pos) if pos < self.len() => res.push(pos),
+                Some(_) => {}
+                None => stack.extend(self.nodes[nid].children.values()),
+            }
+        }
+        res.sort_unstable();
+        res
+    }
+}
+
+impl<T> WeinerTree<T>
+where
+    T: Ord + Hash + Clone + Label,
+{
+    fn estr(&self, nid: usize) -> String {
+        let terminator = if self.is_leaf(nid) { "$" } else { "" };
+        T::label(&self.edge_label(nid)) + terminator
+    }
+
+    fn estr_all(&self, nid: usize) -> String {
+        let terminator = if self.is_leaf(nid) { "$" } else { "" };
+        T::label(&self.label(nid)) + terminator
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut nodes = vec![];
+        let mut edges = vec![];
+        for i in 0..self.nodes.len() {
+            nodes.push(self.estr_all(i));
+            if let Some(pid) = self.nodes[i].parent {
+                edges.push((self.estr_all(pid), self.estr(i), self.estr_all(i)));
+            }
+        }
+        StreeSerde { nodes, edges }.serialize()
+    }
+}
+
+impl<T> Default for WeinerTree<T>
+where
+    T: Ord + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_weiner() {
+    let mut tree = WeinerTree::new();
+    for &c in br"anana".iter().rev() {
+        tree.prepend(c);
+    }
+    assert_eq!(tree.text(), br"anana".to_vec());
+    assert_eq!(tree.locate(br"ana"), vec![0, 2]);
+    tree.prepend(b'b');
+    assert_eq!(tree.locate(br"ana"), vec![1, 3]);
+    assert_eq!(tree.locate(br"ban"), vec![0]);
+    assert!(tree.locate(br"nab").is_empty());
+    let nid = tree.find(br"an").unwrap();
+    assert_eq!(tree.label(nid), br"ana".to_vec());
+    let link = tree.link(tree.find(br"na").unwrap(), &b'a').unwrap();
+    assert_eq!(link, nid);
+}
+
+#[test]
+fn test_weiner_ukkonen() {
+    use super::ukkonnen_vec_lib::Tree;
+    use crate::commons::enum_strs_len_leq;
+    use crate::fib::fibstr_ab;
+
+    let sorted = |json: String| {
+        let mut stree: StreeSerde = serde_json::from_str(&json).unwrap();
+        stree.nodes.sort();
+        stree.edges.sort();
+        (stree.nodes, stree.edges)
+    };
+    let mut texts = vec![br"mississippi".to_vec(), fibstr_ab(10)];
+    texts.extend(enum_strs_len_leq(br"abc", 6));
+    for text in texts {
+        let tree1 = Tree::from_text_finished(&text);
+        let tree2 = WeinerTree::from_text(&text);
+        assert_eq!(sorted(tree1.serialize()), sorted(tree2.serialize()));
+        for pat in enum_strs_len_leq(br"abc", 3) {
+            assert_eq!(tree1.locate(&pat), tree2.locate(&pat));
+        }
+    }
+}
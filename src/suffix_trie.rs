@@ -1,21 +1,262 @@
-// use std::collections::HashSet;
-
-// use crate::bstr::*;
-// use crate::stree_json;
-
-// pub fn suffix_trie<T>(sigma: &HashSet<&bstr>, subs: &HashSet<&bstr>) -> stree_json::StreeSerde {
-//     let nodes: Vec<String> = subs.iter().map(|&s| bstr2string(s)).collect();
-//     let mut edges: Vec<(String, String, String)> = Vec::new();
-//     for &v in subs.iter() {
-//         let vstr = String::from_utf8(v.to_vec()).expect("can be parsed.");
-//         for &c in sigma.iter() {
-//             let cstr = String::from_utf8(c.to_vec()).expect("can be parsed.");
-//             let vcstr = String::from_utf8([v, c].concat()).expect("can be parsed.");
-//             let child = vcstr.as_bytes();
-//             if subs.contains(child) {
-//                 edges.push((vstr.clone(), cstr, vcstr));
-//             }
-//         }
-//     }
-//     stree_json::StreeSerde { nodes, edges }
-// }
+//! Suffix trie
+//!
+//! The suffix trie of a string $T$ is the trie of all the suffixes of $T$,
+//! whose nodes correspond one-to-one to the substrings of $T$.
+//! It has $O(n^2)$ nodes, so it is intended for small texts, e.g., teaching materials.
+//! The underlying [`Trie`] can also be built from any set of strings, e.g., patterns of the Aho–Corasick automaton.
+
+use std::collections::BTreeMap;
+
+use crate::stree_json::{Label, StreeSerde};
+
+struct Node<T> {
+    parent: Option<usize>,
+    /// Character of the edge from the parent.
+    char: Option<T>,
+    depth: usize,
+    children: BTreeMap<T, usize>,
+    /// IDs of the inserted strings that end at the node.
+    ends: Vec<usize>,
+}
+
+impl<T> Node<T> {
+    fn new(parent: Option<usize>, char: Option<T>, depth: usize) -> Self {
+        Self {
+            parent,
+            char,
+            depth,
+            children: BTreeMap::new(),
+            ends: vec![],
+        }
+    }
+}
+
+/// Uncompacted trie, whose edges are labeled by single characters.
+///
+/// Nodes are identified by `usize` IDs, where `0` is the root,
+/// and strings are identified by the order of their insertion.
+pub struct Trie<T> {
+    nodes: Vec<Node<T>>,
+    num_strs: usize,
+}
+
+impl<T> Trie<T>
+where
+    T: Ord + Clone,
+{
+    /// Creates a trie having the root only.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::new(None, None, 0)],
+            num_strs: 0,
+        }
+    }
+
+    /// Builds the trie of given strings, where the ID of a string is its index.
+    pub fn from_strs<S>(strs: &[S]) -> Self
+    where
+        S: AsRef<[T]>,
+    {
+        let mut trie = Self::new();
+        strs.iter().for_each(|s| {
+            trie.insert(s.as_ref());
+        });
+        trie
+    }
+
+    /// Builds the suffix trie of a given text in $O(n^2)$ time,
+    /// where the ID of a suffix is its beginning position.
+    pub fn from_text(text: &[T]) -> Self {
+        let mut trie = Self::new();
+        (0..text.len()).for_each(|i| {
+            trie.insert(&text[i..]);
+        });
+        trie
+    }
+
+    /// Inserts a string and returns the node where it ends.
+    pub fn insert(&mut self, s: &[T]) -> usize {
+        let mut nid = self.root();
+        for c in s {
+            nid = match self.child(nid, c) {
+                Some(cid) => cid,
+                None => {
+                    let cid = self.nodes.len();
+                    let depth = self.nodes[nid].depth + 1;
+                    self.nodes
+                        .push(Node::new(Some(nid), Some(c.clone()), depth));
+                    self.nodes[nid].children.insert(c.clone(), cid);
+                    cid
+                }
+            };
+        }
+        self.nodes[nid].ends.push(self.num_strs);
+        self.num_strs += 1;
+        nid
+    }
+
+    /// Returns the ID of the root.
+    pub const fn root(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of inserted strings.
+    pub fn num_strs(&self) -> usize {
+        self.num_strs
+    }
+
+    pub fn is_leaf(&self, nid: usize) -> bool {
+        self.nodes[nid].children.is_empty()
+    }
+
+    pub fn parent(&self, nid: usize) -> Option<usize> {
+        self.nodes[nid].parent
+    }
+
+    /// Returns the character of the edge to node `nid`.
+    pub fn char(&self, nid: usize) -> Option<&T> {
+        self.nodes[nid].char.as_ref()
+    }
+
+    /// Returns the depth of node `nid`, i.e., the length of its label.
+    pub fn depth(&self, nid: usize) -> usize {
+        self.nodes[nid].depth
+    }
+
+    /// Returns the child of node `nid` whose edge is labeled by `c`.
+    pub fn child(&self, nid: usize, c: &T) -> Option<usize> {
+        self.nodes[nid].children.get(c).copied()
+    }
+
+    /// Returns the pairs of the edge characters and children of node `nid` in the order of the characters.
+    pub fn children(&self, nid: usize) -> impl Iterator<Item = (&T, usize)> + '_ {
+        self.nodes[nid].children.iter().map(|(c, &cid)| (c, cid))
+    }
+
+    /// Returns the IDs of the inserted strings that end at node `nid`.
+    pub fn ends(&self, nid: usize) -> &[usize] {
+        &self.nodes[nid].ends
+    }
+
+    /// Returns the label of node `nid`, i.e., the string spelled from the root to the node.
+    pub fn label(&self, nid: usize) -> Vec<T> {
+        let mut res = vec![];
+        let mut nid = nid;
+        while let Some(c) = self.char(nid) {
+            res.push(c.clone());
+            nid = self.parent(nid).unwrap();
+        }
+        res.reverse();
+        res
+    }
+
+    /// Returns the node whose label is a given string.
+    pub fn find(&self, s: &[T]) -> Option<usize> {
+        s.iter().try_fold(self.root(), |nid, c| self.child(nid, c))
+    }
+
+    /// Returns whether a given string is a prefix of an inserted string,
+    /// i.e., a substring of the text for a suffix trie.
+    pub fn contains(&self, s: &[T]) -> bool {
+        self.find(s).is_some()
+    }
+}
+
+impl<T> Trie<T>
+where
+    T: Ord + Clone + Label,
+{
+    /// Returns the trie as a tree structure, whose nodes are identified by their labels.
+    pub fn to_stree(&self) -> StreeSerde {
+        let labels: Vec<String> = (0..self.nodes.len())
+            .map(|nid| T::label(&self.label(nid)))
+            .collect();
+        let mut edges = vec![];
+        for (nid, label) in labels.iter().enumerate() {
+            for (c, cid) in self.children(nid) {
+                edges.push((
+                    label.clone(),
+                    T::label(std::slice::from_ref(c)),
+                    labels[cid].clone(),
+                ));
+            }
+        }
+        StreeSerde {
+            nodes: labels,
+            edges,
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        self.to_stree().serialize()
+    }
+}
+
+impl<T> Default for Trie<T>
+where
+    T: Ord + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_trie() {
+    let trie = Trie::from_strs(&["he", "she", "his", "hers"].map(str::as_bytes));
+    assert_eq!(trie.num_strs(), 4);
+    assert_eq!(trie.num_nodes(), 10);
+    let h = trie.find(br"h").unwrap();
+    let children: Vec<(u8, Vec<u8>)> = trie
+        .children(h)
+        .map(|(&c, cid)| (c, trie.label(cid)))
+        .collect();
+    assert_eq!(
+        children,
+        vec![(b'e', br"he".to_vec()), (b'i', br"hi".to_vec())]
+    );
+    assert_eq!(trie.ends(trie.find(br"he").unwrap()), &[0]);
+    assert_eq!(trie.ends(trie.find(br"hers").unwrap()), &[3]);
+    assert!(trie.ends(trie.find(br"her").unwrap()).is_empty());
+    assert!(!trie.contains(br"hs"));
+    assert_eq!(trie.depth(trie.find(br"she").unwrap()), 3);
+}
+
+#[test]
+fn test_suffix_trie() {
+    use crate::commons::{alphabet_set, enum_strs_len_leq, substrs};
+    use std::collections::HashSet;
+
+    let trie = Trie::from_text(br"abab");
+    assert_eq!(trie.ends(trie.find(br"ab").unwrap()), &[2]);
+    assert_eq!(trie.ends(trie.find(br"bab").unwrap()), &[1]);
+    assert!(trie.ends(trie.find(br"aba").unwrap()).is_empty());
+
+    let mut texts = vec![br"banana".to_vec(), br"abracadabra".to_vec()];
+    texts.extend(enum_strs_len_leq(br"ab", 6));
+    for text in texts {
+        let trie = Trie::from_text(&text);
+        let mut subs = substrs(&text);
+        subs.insert(vec![]);
+        let nodes: HashSet<String> = subs.iter().map(|x| u8::label(x)).collect();
+        let mut edges = HashSet::new();
+        for v in subs.iter() {
+            for c in alphabet_set(&text) {
+                let vc = [v.clone(), vec![c]].concat();
+                if subs.contains(&vc) {
+                    edges.insert((u8::label(v), u8::label(&[c]), u8::label(&vc)));
+                }
+            }
+        }
+        let stree = trie.to_stree();
+        assert_eq!(stree.nodes.len(), nodes.len());
+        assert_eq!(stree.nodes.into_iter().collect::<HashSet<_>>(), nodes);
+        assert_eq!(stree.edges.len(), edges.len());
+        assert_eq!(stree.edges.into_iter().collect::<HashSet<_>>(), edges);
+    }
+}
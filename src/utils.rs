@@ -1,10 +1,5 @@
-use std::convert::TryFrom;
-
 /// Decodes byte characters as ascii characters.
-/// if a byte cannot be parsed, it replaced as a character '?'.
+/// Every byte is decoded as the character of the same code point, i.e., bytes are read as Latin-1.
 pub fn decode_ascii_force(bytes: &[u8]) -> String {
-    bytes
-        .iter()
-        .map(|c| char::try_from(*c).unwrap_or('?'))
-        .collect()
+    bytes.iter().map(|&c| char::from(c)).collect()
 }